@@ -1,7 +1,7 @@
 .PHONY: check
 check: ## dev: do we compile?
 	@$(call log,"checking compilation")
-	@cargo check --all --tests --benches
+	@cargo check --all --all-features --tests --benches
 
 .PHONY: clean
 clean: ## dev: purge build artifacts
@@ -26,9 +26,14 @@ format-check: ## dev: are we pretty?
 .PHONY: test
 test: ## dev: do we profess to work?
 	@$(call log,"running all tests")
-	@cargo nextest run --profile ci --workspace
+	@cargo nextest run --profile ci --workspace --all-features
+
+.PHONY: test-no-std
+test-no-std: ## dev: do we work without std?
+	@$(call log,"running no_std tests")
+	@cargo nextest run --profile ci -p rawr-resource-name -p rawr-acm --no-default-features
 
 .PHONY: test-docs
 test-docs: ## dev: do our docs work?
 	@$(call log,"running all documentation tests")
-	@cargo test --doc --workspace
\ No newline at end of file
+	@cargo test --doc --workspace --all-features
\ No newline at end of file
@@ -1,8 +1,9 @@
 use criterion::{Criterion, Throughput, criterion_group, criterion_main};
 use std::hint::black_box;
 
-use rawr_acm::Acm;
+use rawr_acm::{Acm, AcmSnapshot};
 
+#[derive(Clone)]
 struct TestPolicy<'a> {
     effect: &'a str,
     action: &'a str,
@@ -17,6 +18,16 @@ struct TestCase<'a> {
 }
 
 fn bench(c: &mut Criterion) {
+    let many_rules = many_rules();
+    let many_rules: Vec<TestPolicy<'_>> = many_rules
+        .iter()
+        .map(|(action, resource)| TestPolicy {
+            effect: "allow",
+            action,
+            resource,
+        })
+        .collect();
+
     let test_cases = vec![
         TestCase {
             name: "simple_allow",
@@ -75,18 +86,72 @@ fn bench(c: &mut Criterion) {
                 resource: "a/b/c/d/e/f/g/h/i",
             }],
         },
+        TestCase {
+            name: "many_rules_literal",
+            enforce_action: "identity:GetMember",
+            enforce_resource: "tenants/512/members/50",
+            policies: many_rules.clone(),
+        },
+        TestCase {
+            name: "many_rules_glob",
+            enforce_action: "identity:GetTeam",
+            enforce_resource: "tenants/512/teams/team-51-ceres",
+            policies: many_rules,
+        },
     ];
 
     let mut group = c.benchmark_group("Acm::enforce");
-    for case in test_cases {
-        let mut acm = Acm::new();
-        for policy in &case.policies {
-            match policy.effect {
-                "allow" => acm.allow(policy.action, policy.resource),
-                "deny" => acm.deny(policy.action, policy.resource),
-                _ => panic!("Unknown effect: {}", policy.effect),
-            }
-        }
+    for case in &test_cases {
+        let acm = build(&case.policies);
+
+        group.throughput(Throughput::Elements(1));
+        group.bench_function(case.name, |b| {
+            b.iter(|| {
+                acm.enforce(
+                    black_box(case.enforce_action),
+                    black_box(case.enforce_resource),
+                );
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("FrozenAcm::enforce");
+    for case in &test_cases {
+        let acm = build(&case.policies).freeze();
+
+        group.throughput(Throughput::Elements(1));
+        group.bench_function(case.name, |b| {
+            b.iter(|| {
+                acm.enforce(
+                    black_box(case.enforce_action),
+                    black_box(case.enforce_resource),
+                );
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("AcmSnapshot::enforce");
+    for case in &test_cases {
+        let bytes = build(&case.policies).freeze().to_snapshot();
+        let acm = AcmSnapshot::load(&bytes).unwrap();
+
+        group.throughput(Throughput::Elements(1));
+        group.bench_function(case.name, |b| {
+            b.iter(|| {
+                acm.enforce(
+                    black_box(case.enforce_action),
+                    black_box(case.enforce_resource),
+                );
+            })
+        });
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("CompiledAcm::enforce");
+    for case in &test_cases {
+        let acm = build(&case.policies).compile();
 
         group.throughput(Throughput::Elements(1));
         group.bench_function(case.name, |b| {
@@ -101,6 +166,34 @@ fn bench(c: &mut Criterion) {
     group.finish();
 }
 
+fn build(policies: &[TestPolicy<'_>]) -> Acm {
+    let mut acm = Acm::new();
+    for policy in policies {
+        match policy.effect {
+            "allow" => acm.allow(policy.action, policy.resource),
+            "deny" => acm.deny(policy.action, policy.resource),
+            _ => panic!("Unknown effect: {}", policy.effect),
+        }
+    }
+    acm
+}
+
+/// a large rule set: a team prefix glob and a literal member under each of
+/// 1,000 tenants, 100k rules in all.
+fn many_rules() -> Vec<(String, String)> {
+    (0..1_000)
+        .flat_map(|tenant| {
+            (0..100).map(move |member| {
+                let resource = match member % 2 {
+                    0 => format!("tenants/{tenant}/members/{member}"),
+                    _ => format!("tenants/{tenant}/teams/team-{member}-*"),
+                };
+                ("identity:Get*".to_string(), resource)
+            })
+        })
+        .collect()
+}
+
 criterion_group!(benches, bench);
 
 criterion_main!(benches);
@@ -1,9 +1,17 @@
+use alloc::vec::Vec;
+
 use smallvec::SmallVec;
 
+use crate::analysis::{Finding, analyze};
+use crate::combine::{CombinedAcm, Combining};
+use crate::dfa::CompiledAcm;
+use crate::frozen::FrozenAcm;
+use crate::glob::InvalidGlob;
+use crate::query::{Grant, Query};
 use crate::trie::{STACK_CAPACITY, Trie};
 
-const ACTION_SEPARATOR: &str = ":";
-const RESOURCE_SEPARATOR: &str = "/";
+pub(crate) const ACTION_SEPARATOR: &str = ":";
+pub(crate) const RESOURCE_SEPARATOR: &str = "/";
 
 #[derive(Debug)]
 pub struct Acm {
@@ -26,19 +34,23 @@ impl Acm {
     }
 
     pub fn allow(&mut self, action: &str, resource_path: &str) {
-        self.allow.insert(
-            action
-                .split(ACTION_SEPARATOR)
-                .chain(resource_path.split(RESOURCE_SEPARATOR)),
-        );
+        self.allow
+            .insert(
+                action
+                    .split(ACTION_SEPARATOR)
+                    .chain(resource_path.split(RESOURCE_SEPARATOR)),
+            )
+            .record_split(action.split(ACTION_SEPARATOR).count());
     }
 
     pub fn deny(&mut self, action: &str, resource_path: &str) {
-        self.deny.insert(
-            action
-                .split(ACTION_SEPARATOR)
-                .chain(resource_path.split(RESOURCE_SEPARATOR)),
-        );
+        self.deny
+            .insert(
+                action
+                    .split(ACTION_SEPARATOR)
+                    .chain(resource_path.split(RESOURCE_SEPARATOR)),
+            )
+            .record_split(action.split(ACTION_SEPARATOR).count());
     }
 
     pub fn enforce(&self, action: &str, resource_path: &str) -> bool {
@@ -48,13 +60,18 @@ impl Acm {
             .chain(resource_path.split(RESOURCE_SEPARATOR))
             .collect();
 
+        self.grants(&segments)
+    }
+
+    /// whether a request, already split into segments, is granted.
+    pub(crate) fn grants(&self, segments: &SmallVec<[&str; STACK_CAPACITY]>) -> bool {
         // check the watch list
-        if self.deny.contains(&segments) {
+        if self.deny.contains(segments) {
             return false;
         }
 
         // okay, now check "the list"
-        self.allow.contains(&segments)
+        self.allow.contains(segments)
     }
 
     pub fn enforce_batch(&self, requests: &[(&str, &str)]) -> Vec<bool> {
@@ -69,16 +86,98 @@ impl Acm {
                     .chain(resource_path.split(RESOURCE_SEPARATOR)),
             );
 
-            let granted = !self.deny.contains(&segments) && self.allow.contains(&segments);
-            results.push(granted);
+            results.push(self.grants(&segments));
         }
 
         results
     }
+
+    /// a request is granted if either matrix grants it.
+    ///
+    /// each matrix is evaluated on its own, so one matrix's deny rules don't
+    /// cancel the other's allow rules, see `CombinedAcm`.
+    pub fn union(self, other: Acm) -> CombinedAcm {
+        self.combine(Combining::AnyOf, other)
+    }
+
+    /// a request is granted only if both matrices grant it.
+    ///
+    /// the intersection of two sets of globs generally isn't a set of globs,
+    /// so rather than merging rules this evaluates both, see `CombinedAcm`.
+    pub fn intersect(self, other: Acm) -> CombinedAcm {
+        self.combine(Combining::AllOf, other)
+    }
+
+    fn combine(self, combining: Combining, other: Acm) -> CombinedAcm {
+        let mut combined = CombinedAcm::new(combining);
+        combined.push(self);
+        combined.push(other);
+        combined
+    }
+
+    /// this matrix, with every rule of `other` added. a request is granted
+    /// if either matrix allows it and _neither_ denies it, so unlike
+    /// `Acm::union` a deny rule in one matrix overrides allow rules in both.
+    pub fn merge_rules(mut self, other: &Acm) -> Acm {
+        self.allow.extend(&other.allow);
+        self.deny.extend(&other.deny);
+        self
+    }
+
+    /// this matrix, with the deny rules of `other` added. a request is
+    /// granted if this matrix grants it, and `other` doesn't deny it (e.g. an
+    /// org-wide guardrail).
+    pub fn merge_denies(mut self, other: &Acm) -> Acm {
+        self.deny.extend(&other.deny);
+        self
+    }
+
+    /// compile the current rules into a read-only `FrozenAcm`, laid out for
+    /// cache-friendly evaluation. later changes to this `Acm` aren't
+    /// reflected in it.
+    pub fn freeze(&self) -> FrozenAcm {
+        FrozenAcm::new(&self.allow, &self.deny)
+    }
+
+    /// every allow rule which may grant a request for `action` on a resource
+    /// under `resource_prefix`, less those a deny rule certainly overrides.
+    ///
+    /// `action` and `resource_prefix` are patterns, e.g. `"project:*"` and
+    /// `"resource/projects/*"`. rules are matched segment by segment, and may
+    /// continue beyond the prefix. a grant which some deny rule may override
+    /// is marked `Grant::is_partially_denied`. a glob only covers another
+    /// glob if it's the same pattern, or `*`, so results err towards
+    /// "partially" denied. a rule inserted with its segments split between
+    /// action and resource in several ways is granted once per split.
+    ///
+    /// errors if a segment of the query has an invalid glob, since queries
+    /// are typically user input.
+    pub fn query(&self, action: &str, resource_prefix: &str) -> Result<Vec<Grant>, InvalidGlob> {
+        Ok(Query::new(action, resource_prefix)?.run(&self.allow, &self.deny))
+    }
+
+    /// report rules which are inserted more than once, subsumed by a broader
+    /// rule with the same effect, or (for allow rules) entirely cancelled by
+    /// a deny rule.
+    ///
+    /// each finding is certain: a glob only covers another glob if it's the
+    /// same pattern, or `*`.
+    pub fn analyze(&self) -> Vec<Finding> {
+        analyze(&self.allow, &self.deny)
+    }
+
+    /// compile the current rules into a read-only `CompiledAcm`, which
+    /// evaluates each request in a single pass over its segments. later
+    /// changes to this `Acm` aren't reflected in it.
+    pub fn compile(&self) -> CompiledAcm {
+        CompiledAcm::new(&self.allow, &self.deny)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use alloc::vec;
+
     use super::*;
 
     #[test]
@@ -98,6 +197,78 @@ mod tests {
         assert!(!acm.enforce("action:Delete", "resource/sensitive"));
     }
 
+    #[test]
+    fn test_any_matching_rule_grants() {
+        let mut acm = Acm::new();
+        acm.allow("action:Get", "*/path/deeper");
+        acm.allow("action:Get", "resource/path");
+        assert!(acm.enforce("action:Get", "resource/path"));
+        assert!(acm.enforce("action:Get", "other/path/deeper"));
+        assert!(!acm.enforce("action:Get", "other/path"));
+    }
+
+    #[test]
+    fn test_union() {
+        let mut identity = Acm::new();
+        identity.allow("action:Get", "resource/*");
+        identity.deny("action:Put", "resource/*");
+        let mut team = Acm::new();
+        team.allow("action:Put", "resource/*");
+        team.deny("action:Put", "resource/frozen");
+
+        let acm = identity.union(team);
+        assert!(acm.enforce("action:Get", "resource/path"));
+        // identity's deny doesn't cancel what the team grants
+        assert!(acm.enforce("action:Put", "resource/path"));
+        assert!(!acm.enforce("action:Put", "resource/frozen"));
+        assert!(!acm.enforce("action:Delete", "resource/path"));
+    }
+
+    #[test]
+    fn test_merge_rules() {
+        let mut identity = Acm::new();
+        identity.allow("action:Get", "resource/*");
+        identity.deny("action:Put", "resource/*");
+        let mut team = Acm::new();
+        team.allow("action:Put", "resource/*");
+        team.deny("action:Get", "resource/sensitive");
+
+        let acm = identity.merge_rules(&team);
+        assert!(acm.enforce("action:Get", "resource/path"));
+        assert!(!acm.enforce("action:Get", "resource/sensitive"));
+        // denies override across matrices
+        assert!(!acm.enforce("action:Put", "resource/path"));
+        assert!(!acm.enforce("action:Delete", "resource/path"));
+    }
+
+    #[test]
+    fn test_merge_denies() {
+        let mut identity = Acm::new();
+        identity.allow("action:*", "resource/*");
+        let mut guardrail = Acm::new();
+        guardrail.allow("action:*", "*/*");
+        guardrail.deny("action:Delete", "resource/*");
+
+        let acm = identity.merge_denies(&guardrail);
+        assert!(acm.enforce("action:Get", "resource/path"));
+        assert!(!acm.enforce("action:Delete", "resource/path"));
+        // only the guardrail's denies carry over
+        assert!(!acm.enforce("action:Get", "other/path"));
+    }
+
+    #[test]
+    fn test_intersect() {
+        let mut identity = Acm::new();
+        identity.allow("action:*", "resource/*");
+        let mut boundary = Acm::new();
+        boundary.allow("action:Get", "*/*");
+
+        let acm = identity.intersect(boundary);
+        assert!(acm.enforce("action:Get", "resource/path"));
+        assert!(!acm.enforce("action:Put", "resource/path"));
+        assert!(!acm.enforce("action:Get", "other/path"));
+    }
+
     #[test]
     fn test_enforce_batch() {
         let mut acm = Acm::new();
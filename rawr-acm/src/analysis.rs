@@ -0,0 +1,353 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::acm::{ACTION_SEPARATOR, RESOURCE_SEPARATOR};
+use crate::pattern::{Pattern, join};
+use crate::trie::{Trie, TrieNode};
+
+/// whether a rule allows or denies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Allow,
+    Deny,
+}
+
+/// a rule's action and resource patterns, as inserted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    action: String,
+    resource: String,
+}
+
+impl Rule {
+    fn new(patterns: &[Pattern<'_>], action_len: usize) -> Self {
+        let (action, resource) = patterns.split_at(action_len.min(patterns.len()));
+        Rule {
+            action: join(action, ACTION_SEPARATOR),
+            resource: join(resource, RESOURCE_SEPARATOR),
+        }
+    }
+
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.action, self.resource)
+    }
+}
+
+/// a problem with a policy, found by `Acm::analyze`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Finding {
+    /// the same rule was inserted more than once.
+    Duplicate {
+        effect: Effect,
+        rule: Rule,
+        insertions: usize,
+    },
+
+    /// every request `rule` matches is also matched by `by`, a broader rule
+    /// with the same effect, so `rule` can be removed. this includes rules
+    /// which differ only in where the action ends and the resource begins
+    /// (e.g. `a:b c/d` and `a:b:c d`), since requests are matched segment by
+    /// segment.
+    Subsumed {
+        effect: Effect,
+        rule: Rule,
+        by: Rule,
+    },
+
+    /// every request `allow` matches is denied by `by`, so `allow` never
+    /// grants anything.
+    Denied { allow: Rule, by: Rule },
+}
+
+/// find every `Finding`, in rule order: allow rules, then deny rules.
+pub(crate) fn analyze(allow: &Trie, deny: &Trie) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    for (effect, trie) in [(Effect::Allow, allow), (Effect::Deny, deny)] {
+        trie.for_each_rule(|patterns, end| {
+            let subsumed_by = Cover::find_other(trie, patterns);
+            let denied_by = match effect {
+                Effect::Allow => Cover::find(deny, patterns),
+                Effect::Deny => None,
+            };
+
+            for (idx, split) in end.splits().iter().enumerate() {
+                let rule = || Rule::new(patterns, split.action_len);
+
+                if split.insertions > 1 {
+                    findings.push(Finding::Duplicate {
+                        effect,
+                        rule: rule(),
+                        insertions: split.insertions,
+                    });
+                }
+                // the same segments split differently match the same
+                // requests, only the first split is needed.
+                let by = match idx {
+                    0 => subsumed_by.clone(),
+                    _ => Some(Rule::new(patterns, end.splits()[0].action_len)),
+                };
+                if let Some(by) = by {
+                    findings.push(Finding::Subsumed {
+                        effect,
+                        rule: rule(),
+                        by,
+                    });
+                }
+                if let Some(by) = &denied_by {
+                    findings.push(Finding::Denied {
+                        allow: rule(),
+                        by: by.clone(),
+                    });
+                }
+            }
+        });
+    }
+
+    findings
+}
+
+/// a depth-first search for a rule covering another, i.e. one which
+/// certainly matches every request the other does.
+struct Cover<'r, 't> {
+    rule: &'r [Pattern<'r>],
+    path: Vec<Pattern<'t>>,
+    // whether `rule` itself is in the trie being searched.
+    skip_rule: bool,
+}
+
+impl<'r, 't> Cover<'r, 't> {
+    /// the first rule of `trie` covering `rule`.
+    fn find(trie: &'t Trie, rule: &'r [Pattern<'r>]) -> Option<Rule> {
+        Cover {
+            rule,
+            path: Vec::new(),
+            skip_rule: false,
+        }
+        .run(trie)
+    }
+
+    /// the first rule of `trie`, other than `rule` itself, covering `rule`.
+    fn find_other(trie: &'t Trie, rule: &'r [Pattern<'r>]) -> Option<Rule> {
+        Cover {
+            rule,
+            path: Vec::new(),
+            skip_rule: true,
+        }
+        .run(trie)
+    }
+
+    fn run(mut self, trie: &'t Trie) -> Option<Rule> {
+        let end = self.node(trie.root())?;
+        // a covering rule inserted with several splits is reported with its
+        // first.
+        Some(Rule::new(&self.path, end.splits()[0].action_len))
+    }
+
+    /// push `pattern` onto the path, if it covers the rule's next segment.
+    fn push(&mut self, pattern: Pattern<'t>) -> bool {
+        let covers = self
+            .rule
+            .get(self.path.len())
+            .is_some_and(|segment| pattern.covers(*segment));
+        if covers {
+            self.path.push(pattern);
+        }
+        covers
+    }
+
+    fn node(&mut self, node: &'t TrieNode) -> Option<&'t TrieNode> {
+        let depth = self.path.len();
+        let found = self.search(node);
+        if found.is_none() {
+            self.path.truncate(depth);
+        }
+        found
+    }
+
+    fn search(&mut self, node: &'t TrieNode) -> Option<&'t TrieNode> {
+        for segment in node.tail() {
+            if !self.push(Pattern::Literal(segment)) {
+                return None;
+            }
+        }
+
+        if self.path.len() == self.rule.len() {
+            let is_rule = self
+                .path
+                .iter()
+                .zip(self.rule)
+                .all(|(a, b)| a.raw() == b.raw());
+            return (node.is_terminal() && !(self.skip_rule && is_rule)).then_some(node);
+        }
+
+        let children = node
+            .literal_children()
+            .iter()
+            .map(|(_, child)| child)
+            .chain(node.glob_children());
+        for child in children {
+            let depth = self.path.len();
+            if self.push(Pattern::of(child))
+                && let Some(end) = self.node(child)
+            {
+                return Some(end);
+            }
+            self.path.truncate(depth);
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    use super::*;
+    use crate::Acm;
+
+    fn rule(action: &str, resource: &str) -> Rule {
+        Rule {
+            action: action.to_string(),
+            resource: resource.to_string(),
+        }
+    }
+
+    #[test]
+    fn analyze_clean_policy() {
+        let mut acm = Acm::new();
+        acm.allow("project:Get", "projects/*");
+        acm.allow("project:Put", "projects/*");
+        acm.deny("project:Get", "projects/secret");
+        assert_eq!(acm.analyze(), vec![]);
+    }
+
+    #[test]
+    fn analyze_duplicates() {
+        let mut acm = Acm::new();
+        acm.allow("project:Get", "projects/*");
+        acm.allow("project:Get", "projects/*");
+        acm.allow("project:Get", "projects/*");
+        acm.deny("project:Get", "projects/secret");
+        acm.deny("project:Get", "projects/secret");
+
+        assert_eq!(
+            acm.analyze(),
+            vec![
+                Finding::Duplicate {
+                    effect: Effect::Allow,
+                    rule: rule("project:Get", "projects/*"),
+                    insertions: 3,
+                },
+                Finding::Duplicate {
+                    effect: Effect::Deny,
+                    rule: rule("project:Get", "projects/secret"),
+                    insertions: 2,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn analyze_subsumed_rules() {
+        let mut acm = Acm::new();
+        acm.allow("project:*", "projects/*");
+        acm.allow("project:Get", "projects/alpha");
+        acm.allow("project:Get", "projects/team-*");
+        acm.allow("project:Get", "projects/alpha/members");
+        acm.deny("*:*", "projects/secret");
+        acm.deny("project:Delete", "projects/secret");
+
+        assert_eq!(
+            acm.analyze(),
+            vec![
+                Finding::Subsumed {
+                    effect: Effect::Allow,
+                    rule: rule("project:Get", "projects/alpha"),
+                    by: rule("project:*", "projects/*"),
+                },
+                Finding::Subsumed {
+                    effect: Effect::Allow,
+                    rule: rule("project:Get", "projects/team-*"),
+                    by: rule("project:*", "projects/*"),
+                },
+                Finding::Subsumed {
+                    effect: Effect::Deny,
+                    rule: rule("project:Delete", "projects/secret"),
+                    by: rule("*:*", "projects/secret"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn analyze_denied_allows() {
+        let mut acm = Acm::new();
+        acm.allow("project:Delete", "projects/production");
+        acm.allow("project:Delete", "projects/staging-*");
+        acm.allow("project:Delete", "projects/*");
+        acm.deny("project:Delete", "projects/prod*");
+        acm.deny("project:Delete", "projects/staging-*");
+
+        let findings = acm.analyze();
+        assert!(findings.contains(&Finding::Denied {
+            allow: rule("project:Delete", "projects/production"),
+            by: rule("project:Delete", "projects/prod*"),
+        }));
+        assert!(findings.contains(&Finding::Denied {
+            allow: rule("project:Delete", "projects/staging-*"),
+            by: rule("project:Delete", "projects/staging-*"),
+        }));
+        // only partially denied
+        assert!(!findings.iter().any(|finding| matches!(
+            finding,
+            Finding::Denied { allow, .. } if allow.resource() == "projects/*"
+        )));
+    }
+
+    #[test]
+    fn analyze_keeps_every_split() {
+        let mut acm = Acm::new();
+        acm.allow("a:b", "c/d");
+        acm.allow("a:b:c", "d");
+        acm.allow("a:b:c", "d");
+
+        // the rule inserted once isn't a duplicate, but either split makes
+        // the other redundant
+        assert_eq!(
+            acm.analyze(),
+            vec![
+                Finding::Duplicate {
+                    effect: Effect::Allow,
+                    rule: rule("a:b:c", "d"),
+                    insertions: 2,
+                },
+                Finding::Subsumed {
+                    effect: Effect::Allow,
+                    rule: rule("a:b:c", "d"),
+                    by: rule("a:b", "c/d"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn rules_display_action_then_resource() {
+        assert_eq!(
+            rule("project:Get", "projects/*").to_string(),
+            "project:Get projects/*"
+        );
+    }
+}
@@ -0,0 +1,166 @@
+use alloc::vec::Vec;
+
+use smallvec::SmallVec;
+
+use crate::Acm;
+use crate::acm::{ACTION_SEPARATOR, RESOURCE_SEPARATOR};
+use crate::trie::STACK_CAPACITY;
+
+/// how a `CombinedAcm` combines the decisions of its members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Combining {
+    /// granted only if every member grants, e.g. identity rules within a
+    /// permission boundary.
+    AllOf,
+    /// granted if any member grants.
+    AnyOf,
+}
+
+/// several `Acm`s, evaluated together under a `Combining` rule.
+///
+/// `Acm::union` and `Acm::intersect` build one from two matrices. unlike
+/// `Acm::merge_rules` and `Acm::merge_denies`, which merge rules into a single
+/// matrix, each member is evaluated on its own, so one member's deny rules
+/// only affect its own decision. e.g. "identity ∩ boundary − guardrail
+/// denies" is
+///
+/// ```
+/// # use rawr_acm::{Acm, CombinedAcm, Combining};
+/// # let (identity, boundary, guardrail) = (Acm::new(), Acm::new(), Acm::new());
+/// let effective = identity.merge_denies(&guardrail).intersect(boundary);
+/// # assert_eq!(effective.combining(), Combining::AllOf);
+/// ```
+///
+/// a combination without members grants nothing.
+#[derive(Debug)]
+pub struct CombinedAcm {
+    combining: Combining,
+    members: Vec<Acm>,
+}
+
+impl CombinedAcm {
+    pub fn new(combining: Combining) -> Self {
+        CombinedAcm {
+            combining,
+            members: Vec::new(),
+        }
+    }
+
+    pub fn push(&mut self, acm: Acm) {
+        self.members.push(acm);
+    }
+
+    pub fn combining(&self) -> Combining {
+        self.combining
+    }
+
+    pub fn members(&self) -> &[Acm] {
+        &self.members
+    }
+
+    pub fn enforce(&self, action: &str, resource_path: &str) -> bool {
+        // split once, for every member
+        let segments: SmallVec<[&str; STACK_CAPACITY]> = action
+            .split(ACTION_SEPARATOR)
+            .chain(resource_path.split(RESOURCE_SEPARATOR))
+            .collect();
+
+        self.grants(&segments)
+    }
+
+    pub fn enforce_batch(&self, requests: &[(&str, &str)]) -> Vec<bool> {
+        let mut results = Vec::with_capacity(requests.len());
+        let mut segments: SmallVec<[&str; STACK_CAPACITY]> = SmallVec::new();
+
+        for (action, resource_path) in requests {
+            segments.clear();
+            segments.extend(
+                action
+                    .split(ACTION_SEPARATOR)
+                    .chain(resource_path.split(RESOURCE_SEPARATOR)),
+            );
+
+            results.push(self.grants(&segments));
+        }
+
+        results
+    }
+
+    fn grants(&self, segments: &SmallVec<[&str; STACK_CAPACITY]>) -> bool {
+        if self.members.is_empty() {
+            return false;
+        }
+
+        match self.combining {
+            Combining::AllOf => self.members.iter().all(|acm| acm.grants(segments)),
+            Combining::AnyOf => self.members.iter().any(|acm| acm.grants(segments)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+
+    fn members() -> [Acm; 3] {
+        let mut identity = Acm::new();
+        identity.allow("project:*", "projects/*");
+        identity.allow("billing:Get", "accounts/*");
+
+        let mut boundary = Acm::new();
+        boundary.allow("project:*", "*/*");
+        boundary.deny("project:Delete", "*/*");
+
+        let mut guardrail = Acm::new();
+        guardrail.deny("*:*", "projects/production");
+
+        [identity, boundary, guardrail]
+    }
+
+    const REQUESTS: &[(&str, &str)] = &[
+        ("project:Get", "projects/staging"),
+        ("project:Delete", "projects/staging"),
+        ("project:Get", "projects/production"),
+        ("billing:Get", "accounts/acme"),
+    ];
+
+    #[test]
+    fn all_of() {
+        let [identity, boundary, guardrail] = members();
+        let effective = identity.merge_denies(&guardrail).intersect(boundary);
+        assert_eq!(effective.members().len(), 2);
+        assert_eq!(
+            effective.enforce_batch(REQUESTS),
+            vec![true, false, false, false]
+        );
+    }
+
+    #[test]
+    fn any_of() {
+        let [identity, boundary, _] = members();
+        let mut combined = CombinedAcm::new(Combining::AnyOf);
+        combined.push(identity);
+        combined.push(boundary);
+        assert_eq!(
+            combined.enforce_batch(REQUESTS),
+            vec![true, true, true, true]
+        );
+        assert_eq!(
+            REQUESTS
+                .iter()
+                .map(|(action, resource)| combined.enforce(action, resource))
+                .collect::<Vec<_>>(),
+            combined.enforce_batch(REQUESTS)
+        );
+    }
+
+    #[test]
+    fn empty_combinations_grant_nothing() {
+        for combining in [Combining::AllOf, Combining::AnyOf] {
+            let combined = CombinedAcm::new(combining);
+            assert!(!combined.enforce("project:Get", "projects/staging"));
+        }
+    }
+}
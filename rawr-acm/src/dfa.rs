@@ -0,0 +1,468 @@
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use smallvec::SmallVec;
+
+use crate::acm::{ACTION_SEPARATOR, RESOURCE_SEPARATOR};
+use crate::frozen::{FrozenTrie, IndexRange, to_u32};
+use crate::glob::SegmentGlob;
+use crate::trie::{STACK_CAPACITY, Trie, TrieNode};
+
+/// the most states a single automaton may have. compiling a trie which needs
+/// more falls back to evaluating the trie itself.
+pub const DFA_MAX_STATES: usize = 16_384;
+
+/// the most distinct glob patterns a single state may branch on. a state with
+/// `n` globs needs `2^n` transitions (one per combination of globs a segment
+/// can match), so compiling a trie which needs more falls back to evaluating
+/// the trie itself.
+pub const DFA_MAX_STATE_GLOBS: usize = 8;
+
+/// the transition to nowhere: no rule can match from here.
+const DEAD: u32 = u32::MAX;
+
+/// the automaton always starts at its first state.
+const START: u32 = 0;
+
+/// a position within the trie: a node, and how far through its tail we are.
+/// once the whole tail is consumed, the node's children are next.
+type Position = (u32, u32);
+
+/// a `TrieNode`, with its children numbered.
+struct NfaNode<'t> {
+    tail: &'t [String],
+    literals: Vec<(&'t str, u32)>,
+    globs: Vec<(&'t str, u32)>,
+    terminal: bool,
+}
+
+/// number every node beneath (and including) `node`.
+fn flatten<'t>(node: &'t TrieNode, nodes: &mut Vec<NfaNode<'t>>) -> u32 {
+    let id = to_u32(nodes.len());
+    nodes.push(NfaNode {
+        tail: node.tail(),
+        literals: Vec::new(),
+        globs: Vec::new(),
+        terminal: node.is_terminal(),
+    });
+
+    for (segment, child) in node.literal_children() {
+        let child = flatten(child, nodes);
+        nodes[id as usize].literals.push((segment, child));
+    }
+    for child in node.glob_children() {
+        let pattern = child.raw_pattern();
+        let child = flatten(child, nodes);
+        nodes[id as usize].globs.push((pattern, child));
+    }
+    id
+}
+
+#[derive(Debug, Clone, Copy)]
+struct DfaState {
+    // `literal_transitions`, sorted by segment.
+    literals: IndexRange,
+    // the ids of the `globs` this state branches on, one bit of the class
+    // each.
+    globs: IndexRange,
+    // `class_transitions`, indexed by the set of `globs` a segment matches.
+    classes: IndexRange,
+    terminal: bool,
+}
+
+/// a deterministic automaton over request segments, evaluated in exactly one
+/// transition per segment.
+///
+/// each state has a sorted table of literal segments. any other segment is
+/// classified by which of the state's (at most `DFA_MAX_STATE_GLOBS`) globs
+/// it matches, and that class picks the next state.
+#[derive(Debug)]
+struct Dfa {
+    states: Vec<DfaState>,
+    literal_transitions: Vec<(IndexRange, u32)>,
+    state_globs: Vec<u32>,
+    class_transitions: Vec<u32>,
+    strings: String,
+    globs: Vec<SegmentGlob>,
+}
+
+/// the subset construction, turning the trie into a `Dfa`.
+struct DfaBuilder<'t> {
+    nodes: Vec<NfaNode<'t>>,
+    dfa: Dfa,
+    // each state's set of positions, in state order.
+    pending: Vec<Vec<Position>>,
+    state_ids: BTreeMap<Vec<Position>, u32>,
+    interned_strings: BTreeMap<&'t str, IndexRange>,
+    interned_globs: BTreeMap<&'t str, u32>,
+}
+
+impl<'t> DfaBuilder<'t> {
+    fn new(trie: &'t Trie) -> Self {
+        let mut nodes = Vec::new();
+        let root = flatten(trie.root(), &mut nodes);
+        let start = Vec::from([(root, 0)]);
+        DfaBuilder {
+            nodes,
+            dfa: Dfa {
+                states: Vec::new(),
+                literal_transitions: Vec::new(),
+                state_globs: Vec::new(),
+                class_transitions: Vec::new(),
+                strings: String::new(),
+                globs: Vec::new(),
+            },
+            pending: Vec::from([start.clone()]),
+            state_ids: BTreeMap::from([(start, START)]),
+            interned_strings: BTreeMap::new(),
+            interned_globs: BTreeMap::new(),
+        }
+    }
+
+    /// determinize every reachable state, or `None` if the automaton would
+    /// exceed `DFA_MAX_STATES` or `DFA_MAX_STATE_GLOBS`.
+    fn build(mut self) -> Option<Dfa> {
+        let mut next = 0;
+        while next < self.pending.len() {
+            let positions = core::mem::take(&mut self.pending[next]);
+            self.compile_state(&positions)?;
+            next += 1;
+        }
+        Some(self.dfa)
+    }
+
+    fn compile_state(&mut self, positions: &[Position]) -> Option<()> {
+        let mut literal_targets: BTreeMap<&'t str, BTreeSet<Position>> = BTreeMap::new();
+        let mut glob_targets: BTreeMap<&'t str, BTreeSet<Position>> = BTreeMap::new();
+        let mut terminal = false;
+
+        for &(node, offset) in positions {
+            let nfa = &self.nodes[node as usize];
+            if let Some(segment) = nfa.tail.get(offset as usize) {
+                literal_targets
+                    .entry(segment)
+                    .or_default()
+                    .insert((node, offset + 1));
+                continue;
+            }
+
+            terminal |= nfa.terminal;
+            for &(segment, child) in &nfa.literals {
+                literal_targets
+                    .entry(segment)
+                    .or_default()
+                    .insert((child, 0));
+            }
+            for &(pattern, child) in &nfa.globs {
+                glob_targets.entry(pattern).or_default().insert((child, 0));
+            }
+        }
+
+        if glob_targets.len() > DFA_MAX_STATE_GLOBS {
+            return None;
+        }
+        let globs: Vec<(u32, &BTreeSet<Position>)> = glob_targets
+            .iter()
+            .map(|(pattern, targets)| (self.intern_glob(pattern), targets))
+            .collect();
+
+        // a literal segment also follows every glob which matches it.
+        let literals_start = self.dfa.literal_transitions.len();
+        for (segment, targets) in &literal_targets {
+            let mut targets = targets.clone();
+            for (glob, glob_targets) in &globs {
+                if self.dfa.globs[*glob as usize].is_match(segment) {
+                    targets.extend(glob_targets.iter().copied());
+                }
+            }
+            let segment = self.intern_string(segment);
+            let target = self.state_id(targets)?;
+            self.dfa.literal_transitions.push((segment, target));
+        }
+
+        let classes_start = self.dfa.class_transitions.len();
+        for class in 0..1usize << globs.len() {
+            let targets: BTreeSet<Position> = globs
+                .iter()
+                .enumerate()
+                .filter(|(bit, _)| class & (1 << bit) != 0)
+                .flat_map(|(_, (_, targets))| targets.iter().copied())
+                .collect();
+            let target = self.state_id(targets)?;
+            self.dfa.class_transitions.push(target);
+        }
+
+        let globs_start = self.dfa.state_globs.len();
+        self.dfa
+            .state_globs
+            .extend(globs.iter().map(|(glob, _)| *glob));
+
+        self.dfa.states.push(DfaState {
+            literals: IndexRange::new(literals_start..self.dfa.literal_transitions.len()),
+            globs: IndexRange::new(globs_start..self.dfa.state_globs.len()),
+            classes: IndexRange::new(classes_start..self.dfa.class_transitions.len()),
+            terminal,
+        });
+        Some(())
+    }
+
+    /// the id of the state for `positions`, queueing it if it's new.
+    fn state_id(&mut self, positions: BTreeSet<Position>) -> Option<u32> {
+        if positions.is_empty() {
+            return Some(DEAD);
+        }
+        let positions: Vec<Position> = positions.into_iter().collect();
+        if let Some(id) = self.state_ids.get(&positions) {
+            return Some(*id);
+        }
+        if self.pending.len() == DFA_MAX_STATES {
+            return None;
+        }
+
+        let id = to_u32(self.pending.len());
+        self.pending.push(positions.clone());
+        self.state_ids.insert(positions, id);
+        Some(id)
+    }
+
+    fn intern_string(&mut self, segment: &'t str) -> IndexRange {
+        let strings = &mut self.dfa.strings;
+        *self.interned_strings.entry(segment).or_insert_with(|| {
+            let start = strings.len();
+            strings.push_str(segment);
+            IndexRange::new(start..strings.len())
+        })
+    }
+
+    fn intern_glob(&mut self, pattern: &'t str) -> u32 {
+        let globs = &mut self.dfa.globs;
+        *self.interned_globs.entry(pattern).or_insert_with(|| {
+            globs.push(SegmentGlob::new(pattern).expect("glob patterns are validated on insert"));
+            to_u32(globs.len() - 1)
+        })
+    }
+}
+
+impl Dfa {
+    fn contains(&self, segments: &[&str]) -> bool {
+        let mut state = START;
+        for segment in segments {
+            let current = &self.states[state as usize];
+
+            let literals = &self.literal_transitions[current.literals.as_range()];
+            state = match literals
+                .binary_search_by(|(key, _)| self.strings[key.as_range()].cmp(segment))
+            {
+                Ok(pos) => literals[pos].1,
+                Err(_) => {
+                    let class = self.state_globs[current.globs.as_range()]
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, glob)| self.globs[**glob as usize].is_match(segment))
+                        .fold(0, |class, (bit, _)| class | (1 << bit));
+                    self.class_transitions[current.classes.as_range()][class]
+                }
+            };
+
+            if state == DEAD {
+                return false;
+            }
+        }
+        self.states[state as usize].terminal
+    }
+}
+
+/// how one side (allow or deny) of a `CompiledAcm` is evaluated.
+#[derive(Debug)]
+enum Evaluator {
+    Dfa(Dfa),
+    // the automaton would have been too large.
+    Trie(FrozenTrie),
+}
+
+impl Evaluator {
+    fn new(trie: &Trie) -> Self {
+        match DfaBuilder::new(trie).build() {
+            Some(dfa) => Evaluator::Dfa(dfa),
+            None => Evaluator::Trie(FrozenTrie::new(trie)),
+        }
+    }
+
+    fn contains(&self, segments: &[&str]) -> bool {
+        match self {
+            Evaluator::Dfa(dfa) => dfa.contains(segments),
+            Evaluator::Trie(trie) => trie.contains(segments),
+        }
+    }
+}
+
+/// a read-only `Acm`, with its allow and deny rules each compiled into a
+/// deterministic automaton. created via `Acm::compile`.
+///
+/// an automaton makes exactly one transition per request segment: a lookup
+/// in a sorted table of literals, and otherwise at most
+/// `DFA_MAX_STATE_GLOBS` glob matches. so unlike the trie, there's no
+/// backtracking, and the cost of `CompiledAcm::enforce` doesn't depend on how
+/// many rules overlap.
+///
+/// rule sets whose automaton would exceed `DFA_MAX_STATES` states, or
+/// `DFA_MAX_STATE_GLOBS` globs in a single state, fall back to a `FrozenAcm`
+/// style trie (for allow and deny independently), see
+/// `CompiledAcm::is_deterministic`.
+#[derive(Debug)]
+pub struct CompiledAcm {
+    allow: Evaluator,
+    deny: Evaluator,
+}
+
+impl CompiledAcm {
+    pub(crate) fn new(allow: &Trie, deny: &Trie) -> Self {
+        CompiledAcm {
+            allow: Evaluator::new(allow),
+            deny: Evaluator::new(deny),
+        }
+    }
+
+    /// whether both the allow and deny rules were compiled into automata,
+    /// rather than falling back to the trie.
+    pub fn is_deterministic(&self) -> bool {
+        matches!(
+            (&self.allow, &self.deny),
+            (Evaluator::Dfa(_), Evaluator::Dfa(_))
+        )
+    }
+
+    pub fn enforce(&self, action: &str, resource_path: &str) -> bool {
+        let segments: SmallVec<[&str; STACK_CAPACITY]> = action
+            .split(ACTION_SEPARATOR)
+            .chain(resource_path.split(RESOURCE_SEPARATOR))
+            .collect();
+
+        !self.deny.contains(&segments) && self.allow.contains(&segments)
+    }
+
+    pub fn enforce_batch(&self, requests: &[(&str, &str)]) -> Vec<bool> {
+        let mut results = Vec::with_capacity(requests.len());
+        let mut segments: SmallVec<[&str; STACK_CAPACITY]> = SmallVec::new();
+
+        for (action, resource_path) in requests {
+            segments.clear();
+            segments.extend(
+                action
+                    .split(ACTION_SEPARATOR)
+                    .chain(resource_path.split(RESOURCE_SEPARATOR)),
+            );
+
+            let granted = !self.deny.contains(&segments) && self.allow.contains(&segments);
+            results.push(granted);
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::Acm;
+
+    const REQUESTS: &[(&str, &str)] = &[
+        ("action:Get", "resource/path"),
+        ("action:Get", "other/path"),
+        ("action:Delete", "resource/sensitive"),
+        ("action:Delete", "resource/normal"),
+        ("identity:DeleteUser", "users/admin"),
+        ("identity:DeleteUser", "users/naomi"),
+        ("identity:CreateUser", "users/team-a/amos"),
+        ("identity:CreateUser", "users/team-/amos"),
+        ("billing:GetInvoice", "accounts/36UeVtK7/invoices/2024-01"),
+        ("billing:PayInvoice", "accounts/36UeVtK7/invoices/2024-01"),
+        ("billing:GetInvoice", "accounts/36UeVtK7/invoices"),
+        ("action:Get", "other/path/deeper"),
+        ("action:Get", "teams/team-c-ops"),
+        ("action:Get", "teams/ops-c"),
+        ("action:Get", "teams/team-c-c"),
+        ("action:Get", "teams/team-f-ops"),
+        ("", ""),
+    ];
+
+    fn acm() -> Acm {
+        let mut acm = Acm::new();
+        acm.allow("action:Get", "resource/path");
+        acm.allow("action:*", "resource/*");
+        acm.allow("identity:*", "users/*");
+        acm.allow("identity:{Create,Update}User*", "users/team-?*/*");
+        acm.allow("billing:Get*", "accounts/*/invoices/*");
+        acm.allow("action:Get", "*/path/deeper");
+        acm.deny("action:Delete", "resource/sensitive");
+        acm.deny("identity:DeleteUser", "users/admin");
+        for team in ["a", "b", "c"] {
+            acm.allow("action:Get", &format!("teams/team-{team}-*"));
+            acm.allow("action:Get", &format!("teams/*-{team}"));
+        }
+        acm
+    }
+
+    #[test]
+    fn compile_agrees_with_acm() {
+        let acm = acm();
+        let compiled = acm.compile();
+        assert!(compiled.is_deterministic());
+
+        for (action, resource) in REQUESTS {
+            assert_eq!(
+                compiled.enforce(action, resource),
+                acm.enforce(action, resource),
+                "{action} {resource}"
+            );
+        }
+        assert_eq!(
+            compiled.enforce_batch(REQUESTS),
+            acm.enforce_batch(REQUESTS)
+        );
+    }
+
+    #[test]
+    fn literals_also_follow_matching_globs() {
+        let mut acm = Acm::new();
+        acm.allow("action:Get", "team-a/members");
+        acm.allow("action:Get", "team-*/invites");
+        let compiled = acm.compile();
+
+        assert!(compiled.enforce("action:Get", "team-a/members"));
+        assert!(compiled.enforce("action:Get", "team-a/invites"));
+        assert!(compiled.enforce("action:Get", "team-b/invites"));
+        assert!(!compiled.enforce("action:Get", "team-b/members"));
+    }
+
+    #[test]
+    fn too_many_globs_fall_back_to_the_trie() {
+        let mut acm = Acm::new();
+        for team in 0..=DFA_MAX_STATE_GLOBS {
+            acm.allow("action:Get", &format!("team-{team}-*"));
+        }
+        acm.deny("action:Get", "team-0-admin");
+        let compiled = acm.compile();
+
+        assert!(!compiled.is_deterministic());
+        assert!(matches!(compiled.deny, Evaluator::Dfa(_)));
+        assert!(compiled.enforce("action:Get", "team-8-ops"));
+        assert!(!compiled.enforce("action:Get", "team-0-admin"));
+        assert!(!compiled.enforce("action:Get", "team-9-ops"));
+    }
+
+    #[test]
+    fn too_many_states_fall_back_to_the_trie() {
+        let mut acm = Acm::new();
+        for idx in 0..DFA_MAX_STATES {
+            acm.allow("action:Get", &format!("resource/{idx}"));
+        }
+        let compiled = acm.compile();
+
+        assert!(!compiled.is_deterministic());
+        assert!(compiled.enforce("action:Get", "resource/42"));
+    }
+}
@@ -0,0 +1,447 @@
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Ordering;
+use core::ops::Range;
+
+use smallvec::SmallVec;
+
+use crate::acm::{ACTION_SEPARATOR, RESOURCE_SEPARATOR};
+#[cfg(feature = "std")]
+use crate::glob::GlobSetMatcher;
+use crate::glob::SegmentGlob;
+#[cfg(feature = "std")]
+use crate::trie::GLOB_SET_THRESHOLD;
+use crate::trie::{STACK_CAPACITY, Trie, TrieNode};
+
+mod snapshot;
+
+pub use snapshot::{AcmSnapshot, SNAPSHOT_VERSION, SnapshotError};
+
+/// the root is always the first node of a frozen trie.
+const ROOT: u32 = 0;
+
+/// a half-open range of indices into one of the arena's arrays.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct IndexRange {
+    start: u32,
+    end: u32,
+}
+
+impl IndexRange {
+    pub(crate) fn new(range: Range<usize>) -> Self {
+        IndexRange {
+            start: to_u32(range.start),
+            end: to_u32(range.end),
+        }
+    }
+
+    pub(crate) fn as_range(self) -> Range<usize> {
+        self.start as usize..self.end as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct FrozenNode {
+    literals: IndexRange,
+    globs: IndexRange,
+    // an index into `glob_sets`, for nodes with enough glob edges to match
+    // them all at once.
+    #[cfg(feature = "std")]
+    glob_set: Option<u32>,
+    // the node's path-compressed literal segments, as a range of `tails`.
+    tail: IndexRange,
+    terminal: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct LiteralEdge {
+    segment: IndexRange,
+    node: u32,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct GlobEdge {
+    glob: u32,
+    node: u32,
+}
+
+/// the arrays of a frozen trie, wherever they're stored: owned by a
+/// `FrozenTrie`, or borrowed from a snapshot.
+trait Arena {
+    fn node(&self, node: u32) -> FrozenNode;
+
+    /// the `idx`th segment of `tails`.
+    fn tail(&self, idx: usize) -> &str;
+
+    /// the `idx`th of `literal_edges`, and its segment.
+    fn literal_edge(&self, idx: usize) -> (&str, u32);
+
+    fn glob_edge(&self, idx: usize) -> GlobEdge;
+
+    fn is_glob_match(&self, glob: u32, segment: &str) -> bool;
+
+    /// the set matching every glob edge of a node, `globs`.
+    #[cfg(feature = "std")]
+    fn glob_set(&self, glob_set: u32, globs: IndexRange) -> &GlobSetMatcher;
+}
+
+/// `TrieNode::contains`, over an arena. tails are checked when a node is
+/// popped, rather than before it's pushed.
+fn contains(arena: &impl Arena, segments: &[&str]) -> bool {
+    let mut stack: SmallVec<[(u32, usize); STACK_CAPACITY]> = SmallVec::new();
+    stack.push((ROOT, 0));
+
+    while let Some((node, idx)) = stack.pop() {
+        let node = arena.node(node);
+
+        let tail = node.tail.as_range();
+        let Some(candidates) = segments.get(idx..idx + tail.len()) else {
+            continue;
+        };
+        if !tail
+            .clone()
+            .zip(candidates)
+            .all(|(expected, candidate)| arena.tail(expected) == *candidate)
+        {
+            continue;
+        }
+        let idx = idx + tail.len();
+
+        if idx == segments.len() {
+            if node.terminal {
+                return true;
+            }
+            continue;
+        }
+
+        let current = segments[idx];
+        if let Some(child) = find_literal(arena, node.literals, current) {
+            stack.push((child, idx + 1));
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(glob_set) = node.glob_set {
+            let globs = node.globs.as_range();
+            arena
+                .glob_set(glob_set, node.globs)
+                .for_each_match(current, |pos| {
+                    stack.push((arena.glob_edge(globs.start + pos).node, idx + 1));
+                });
+            continue;
+        }
+
+        for edge in node.globs.as_range().map(|idx| arena.glob_edge(idx)) {
+            if arena.is_glob_match(edge.glob, current) {
+                stack.push((edge.node, idx + 1));
+            }
+        }
+    }
+
+    false
+}
+
+/// binary search `literals`, which are sorted by segment, for `segment`.
+fn find_literal(arena: &impl Arena, literals: IndexRange, segment: &str) -> Option<u32> {
+    let Range { mut start, mut end } = literals.as_range();
+    while start < end {
+        let mid = start + (end - start) / 2;
+        let (candidate, node) = arena.literal_edge(mid);
+        match candidate.cmp(segment) {
+            Ordering::Less => start = mid + 1,
+            Ordering::Greater => end = mid,
+            Ordering::Equal => return Some(node),
+        }
+    }
+    None
+}
+
+/// a `Trie`, flattened into a handful of contiguous arrays.
+///
+/// nodes are laid out breadth-first, each node's edges are a range of
+/// `literal_edges` (sorted by segment) and `glob_edges` (in insertion order),
+/// and every literal segment and glob pattern lives once in `strings`.
+#[derive(Debug)]
+pub(crate) struct FrozenTrie {
+    nodes: Vec<FrozenNode>,
+    tails: Vec<IndexRange>,
+    literal_edges: Vec<LiteralEdge>,
+    glob_edges: Vec<GlobEdge>,
+    strings: String,
+    globs: Vec<SegmentGlob>,
+    // each of `globs`' pattern, as a range of `strings`.
+    glob_patterns: Vec<IndexRange>,
+    #[cfg(feature = "std")]
+    glob_sets: Vec<GlobSetMatcher>,
+}
+
+impl FrozenTrie {
+    pub(crate) fn new(trie: &Trie) -> Self {
+        let mut frozen = FrozenTrie {
+            nodes: Vec::new(),
+            tails: Vec::new(),
+            literal_edges: Vec::new(),
+            glob_edges: Vec::new(),
+            strings: String::new(),
+            globs: Vec::new(),
+            glob_patterns: Vec::new(),
+            #[cfg(feature = "std")]
+            glob_sets: Vec::new(),
+        };
+        let mut interned_strings: BTreeMap<&str, IndexRange> = BTreeMap::new();
+        let mut intern = |segment| {
+            *interned_strings.entry(segment).or_insert_with(|| {
+                let start = frozen.strings.len();
+                frozen.strings.push_str(segment);
+                IndexRange::new(start..frozen.strings.len())
+            })
+        };
+        let mut interned_globs: BTreeMap<&str, u32> = BTreeMap::new();
+
+        // breadth-first, so node ids are handed out in the same order the
+        // nodes are pushed onto `frozen.nodes`.
+        let mut queue: VecDeque<&TrieNode> = VecDeque::from([trie.root()]);
+        let mut next_id = ROOT + 1;
+        while let Some(node) = queue.pop_front() {
+            let literals_start = frozen.literal_edges.len();
+            for (segment, child) in node.literal_children() {
+                let segment = intern(segment);
+                frozen.literal_edges.push(LiteralEdge {
+                    segment,
+                    node: next_id,
+                });
+                queue.push_back(child);
+                next_id += 1;
+            }
+
+            let globs_start = frozen.glob_edges.len();
+            for child in node.glob_children() {
+                let pattern = child.raw_pattern();
+                let glob = match interned_globs.get(pattern) {
+                    Some(glob) => *glob,
+                    None => {
+                        let glob = SegmentGlob::new(pattern)
+                            .expect("glob patterns are validated on insert");
+                        frozen.globs.push(glob);
+                        frozen.glob_patterns.push(intern(pattern));
+                        let id = to_u32(frozen.globs.len() - 1);
+                        interned_globs.insert(pattern, id);
+                        id
+                    }
+                };
+                frozen.glob_edges.push(GlobEdge {
+                    glob,
+                    node: next_id,
+                });
+                queue.push_back(child);
+                next_id += 1;
+            }
+
+            let tail_start = frozen.tails.len();
+            for segment in node.tail() {
+                frozen.tails.push(intern(segment));
+            }
+
+            #[cfg(feature = "std")]
+            let glob_set = (node.glob_children().len() >= GLOB_SET_THRESHOLD).then(|| {
+                frozen.glob_sets.push(GlobSetMatcher::new(
+                    node.glob_children().iter().map(TrieNode::raw_pattern),
+                ));
+                to_u32(frozen.glob_sets.len() - 1)
+            });
+
+            frozen.nodes.push(FrozenNode {
+                literals: IndexRange::new(literals_start..frozen.literal_edges.len()),
+                globs: IndexRange::new(globs_start..frozen.glob_edges.len()),
+                #[cfg(feature = "std")]
+                glob_set,
+                tail: IndexRange::new(tail_start..frozen.tails.len()),
+                terminal: node.is_terminal(),
+            });
+        }
+
+        frozen
+    }
+
+    pub(crate) fn contains(&self, segments: &[&str]) -> bool {
+        contains(self, segments)
+    }
+}
+
+impl Arena for FrozenTrie {
+    fn node(&self, node: u32) -> FrozenNode {
+        self.nodes[node as usize]
+    }
+
+    fn tail(&self, idx: usize) -> &str {
+        &self.strings[self.tails[idx].as_range()]
+    }
+
+    fn literal_edge(&self, idx: usize) -> (&str, u32) {
+        let edge = self.literal_edges[idx];
+        (&self.strings[edge.segment.as_range()], edge.node)
+    }
+
+    fn glob_edge(&self, idx: usize) -> GlobEdge {
+        self.glob_edges[idx]
+    }
+
+    fn is_glob_match(&self, glob: u32, segment: &str) -> bool {
+        self.globs[glob as usize].is_match(segment)
+    }
+
+    #[cfg(feature = "std")]
+    fn glob_set(&self, glob_set: u32, _: IndexRange) -> &GlobSetMatcher {
+        &self.glob_sets[glob_set as usize]
+    }
+}
+
+/// a read-only `Acm`, compiled into a contiguous arena for cache-friendly
+/// evaluation of large rule sets. created via `Acm::freeze`.
+///
+/// `FrozenAcm::enforce` gives the same answers as `Acm::enforce` for the
+/// rules the `Acm` held when it was frozen.
+#[derive(Debug)]
+pub struct FrozenAcm {
+    allow: FrozenTrie,
+    deny: FrozenTrie,
+}
+
+impl FrozenAcm {
+    pub(crate) fn new(allow: &Trie, deny: &Trie) -> Self {
+        FrozenAcm {
+            allow: FrozenTrie::new(allow),
+            deny: FrozenTrie::new(deny),
+        }
+    }
+
+    pub fn enforce(&self, action: &str, resource_path: &str) -> bool {
+        let segments: SmallVec<[&str; STACK_CAPACITY]> = action
+            .split(ACTION_SEPARATOR)
+            .chain(resource_path.split(RESOURCE_SEPARATOR))
+            .collect();
+
+        !self.deny.contains(&segments) && self.allow.contains(&segments)
+    }
+
+    pub fn enforce_batch(&self, requests: &[(&str, &str)]) -> Vec<bool> {
+        let mut results = Vec::with_capacity(requests.len());
+        let mut segments: SmallVec<[&str; STACK_CAPACITY]> = SmallVec::new();
+
+        for (action, resource_path) in requests {
+            segments.clear();
+            segments.extend(
+                action
+                    .split(ACTION_SEPARATOR)
+                    .chain(resource_path.split(RESOURCE_SEPARATOR)),
+            );
+
+            let granted = !self.deny.contains(&segments) && self.allow.contains(&segments);
+            results.push(granted);
+        }
+
+        results
+    }
+}
+
+pub(crate) fn to_u32(value: usize) -> u32 {
+    u32::try_from(value).expect("acm exceeds u32::MAX entries")
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::vec;
+
+    use crate::Acm;
+
+    fn acm() -> Acm {
+        let mut acm = Acm::new();
+        acm.allow("action:Get", "resource/path");
+        acm.allow("action:*", "resource/*");
+        acm.allow("identity:*", "users/*");
+        acm.allow("identity:{Create,Update}User*", "users/team-?*/*");
+        acm.allow("billing:Get*", "accounts/*/invoices/*");
+        acm.deny("action:Delete", "resource/sensitive");
+        acm.deny("identity:DeleteUser", "users/admin");
+        acm.allow("action:Get", "*/path/deeper");
+        acm.allow(
+            "billing:GetInvoice",
+            "accounts/36UeVtK7/invoices/2024-01/lines",
+        );
+        for team in ["a", "b", "c", "d", "e"] {
+            acm.allow("action:Get", &format!("teams/team-{team}-*"));
+            acm.allow("action:Get", &format!("teams/*-{team}"));
+        }
+        acm
+    }
+
+    #[test]
+    fn freeze_agrees_with_acm() {
+        let acm = acm();
+        let frozen = acm.freeze();
+
+        let requests = [
+            ("action:Get", "resource/path"),
+            ("action:Get", "other/path"),
+            ("action:Delete", "resource/sensitive"),
+            ("action:Delete", "resource/normal"),
+            ("identity:DeleteUser", "users/admin"),
+            ("identity:DeleteUser", "users/naomi"),
+            ("identity:CreateUser", "users/team-a/amos"),
+            ("identity:CreateUser", "users/team-ab/amos"),
+            ("billing:GetInvoice", "accounts/36UeVtK7/invoices/2024-01"),
+            ("billing:PayInvoice", "accounts/36UeVtK7/invoices/2024-01"),
+            ("billing:GetInvoice", "accounts/36UeVtK7/invoices"),
+            ("action:Get", "teams/team-c-ops"),
+            ("action:Get", "teams/ops-c"),
+            ("action:Get", "teams/team-f-ops"),
+            ("action:Get", "other/path/deeper"),
+            ("action:Get", "other/path"),
+            (
+                "billing:GetInvoice",
+                "accounts/36UeVtK7/invoices/2024-01/lines",
+            ),
+            (
+                "billing:GetInvoice",
+                "accounts/36UeVtK7/invoices/2024-01/total",
+            ),
+            ("", ""),
+        ];
+        for (action, resource) in requests {
+            assert_eq!(
+                frozen.enforce(action, resource),
+                acm.enforce(action, resource),
+                "{action} {resource}"
+            );
+        }
+        assert_eq!(
+            frozen.enforce_batch(&requests),
+            acm.enforce_batch(&requests)
+        );
+    }
+
+    #[test]
+    fn freeze_shares_literals_and_globs() {
+        let mut acm = Acm::new();
+        for team in 0..100 {
+            acm.allow("action:Get", &format!("team-{team}/*/members"));
+        }
+        let frozen = acm.freeze();
+
+        // "members" and "*" appear under every team, but are stored once
+        assert_eq!(frozen.allow.globs.len(), 1);
+        assert_eq!(frozen.allow.strings.matches("members").count(), 1);
+        assert!(frozen.enforce("action:Get", "team-42/ceres/members"));
+        assert!(!frozen.enforce("action:Get", "team-100/ceres/members"));
+    }
+
+    #[test]
+    fn freeze_empty_acm() {
+        let frozen = Acm::new().freeze();
+        assert_eq!(
+            frozen.enforce_batch(&[("action:Get", "resource/path")]),
+            vec![false]
+        );
+    }
+}
@@ -0,0 +1,638 @@
+//! a versioned, checksummed binary form of a `FrozenAcm`.
+//!
+//! a snapshot is a 24 byte header followed by a body:
+//!
+//! | offset | size | field                                      |
+//! | ------ | ---- | ------------------------------------------ |
+//! | 0      | 8    | `MAGIC`                                    |
+//! | 8      | 4    | `VERSION`                                  |
+//! | 12     | 4    | body length, in bytes                      |
+//! | 16     | 8    | FNV-1a checksum of the body                |
+//!
+//! the body is the allow trie, then the deny trie. each is seven counts
+//! (nodes, tails, literal edges, glob edges, globs, glob sets and string
+//! bytes) followed by those arrays, in that order. every integer is a
+//! little-endian `u32`, so a snapshot can be read in place from any
+//! alignment, e.g. straight out of a memory map.
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use smallvec::SmallVec;
+use thiserror::Error;
+
+use super::{Arena, FrozenAcm, FrozenNode, FrozenTrie, GlobEdge, IndexRange, contains, to_u32};
+use crate::acm::{ACTION_SEPARATOR, RESOURCE_SEPARATOR};
+#[cfg(feature = "std")]
+use crate::glob::GlobSetMatcher;
+use crate::glob::SegmentGlob;
+use crate::trie::{GLOB_SET_THRESHOLD, STACK_CAPACITY};
+
+/// the first bytes of every snapshot.
+const MAGIC: [u8; 8] = *b"RAWRACM\0";
+
+/// the version of the format written by `FrozenAcm::to_snapshot`, bumped on
+/// every incompatible change.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+const HEADER_LEN: usize = 24;
+
+const WORD: usize = size_of::<u32>();
+
+/// the words of a node: literals, globs, glob set, tail and terminal.
+const NODE_WORDS: usize = 8;
+const TAIL_WORDS: usize = 2;
+const LITERAL_EDGE_WORDS: usize = 3;
+const GLOB_EDGE_WORDS: usize = 2;
+const GLOB_WORDS: usize = 2;
+
+/// a node without a glob set.
+const NO_GLOB_SET: u32 = u32::MAX;
+
+/// FNV-1a 64-bit offset basis.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// FNV-1a 64-bit prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// compiled once, on first use. snapshots are only `Sync` with `std`.
+#[cfg(feature = "std")]
+type Lazy<T> = std::sync::OnceLock<T>;
+#[cfg(not(feature = "std"))]
+type Lazy<T> = core::cell::OnceCell<T>;
+
+/// the ways loading a snapshot can fail.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum SnapshotError {
+    /// the input ends before the header or body does.
+    #[error("snapshot is truncated")]
+    Truncated,
+
+    /// the input doesn't start with the snapshot magic bytes.
+    #[error("not an acm snapshot")]
+    BadMagic,
+
+    /// the snapshot was written by an incompatible version of rawr-acm.
+    #[error("unsupported snapshot version {found}, expected {SNAPSHOT_VERSION}")]
+    UnsupportedVersion { found: u32 },
+
+    /// the body doesn't match the checksum in the header.
+    #[error("snapshot checksum mismatch, expected {expected:#018x} but found {found:#018x}")]
+    ChecksumMismatch { expected: u64, found: u64 },
+
+    /// the checksum is intact, but the body isn't a valid trie.
+    #[error("malformed snapshot: {reason}")]
+    Malformed { reason: &'static str },
+}
+
+/// FNV-1a of `bytes`, the snapshot checksum.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// the `idx`th little-endian word of `bytes`.
+fn word(bytes: &[u8], idx: usize) -> u32 {
+    let at = idx * WORD;
+    u32::from_le_bytes(bytes[at..at + WORD].try_into().expect("a word is 4 bytes"))
+}
+
+/// the range held in the `idx`th pair of words of `bytes`.
+fn range(bytes: &[u8], idx: usize) -> Range<usize> {
+    word(bytes, idx) as usize..word(bytes, idx + 1) as usize
+}
+
+fn push_word(out: &mut Vec<u8>, word: u32) {
+    out.extend_from_slice(&word.to_le_bytes());
+}
+
+fn push_range(out: &mut Vec<u8>, range: IndexRange) {
+    push_word(out, range.start);
+    push_word(out, range.end);
+}
+
+impl FrozenAcm {
+    /// serialize into a snapshot, which `AcmSnapshot::load` can read back
+    /// without copying.
+    pub fn to_snapshot(&self) -> Vec<u8> {
+        let mut body = Vec::new();
+        write_trie(&self.allow, &mut body);
+        write_trie(&self.deny, &mut body);
+
+        let mut out = Vec::with_capacity(HEADER_LEN + body.len());
+        out.extend_from_slice(&MAGIC);
+        push_word(&mut out, SNAPSHOT_VERSION);
+        push_word(&mut out, to_u32(body.len()));
+        out.extend_from_slice(&fnv1a(&body).to_le_bytes());
+        out.extend_from_slice(&body);
+        out
+    }
+}
+
+fn write_trie(trie: &FrozenTrie, out: &mut Vec<u8>) {
+    // glob sets are numbered in node order, independent of whether this
+    // build has any.
+    let has_glob_set = |node: &FrozenNode| node.globs.as_range().len() >= GLOB_SET_THRESHOLD;
+    let glob_sets = trie.nodes.iter().filter(|node| has_glob_set(node)).count();
+
+    for count in [
+        trie.nodes.len(),
+        trie.tails.len(),
+        trie.literal_edges.len(),
+        trie.glob_edges.len(),
+        trie.glob_patterns.len(),
+        glob_sets,
+        trie.strings.len(),
+    ] {
+        push_word(out, to_u32(count));
+    }
+
+    let mut next_glob_set = 0;
+    for node in &trie.nodes {
+        push_range(out, node.literals);
+        push_range(out, node.globs);
+        let glob_set = if has_glob_set(node) {
+            next_glob_set += 1;
+            next_glob_set - 1
+        } else {
+            NO_GLOB_SET
+        };
+        push_word(out, glob_set);
+        push_range(out, node.tail);
+        push_word(out, u32::from(node.terminal));
+    }
+    for tail in &trie.tails {
+        push_range(out, *tail);
+    }
+    for edge in &trie.literal_edges {
+        push_range(out, edge.segment);
+        push_word(out, edge.node);
+    }
+    for edge in &trie.glob_edges {
+        push_word(out, edge.glob);
+        push_word(out, edge.node);
+    }
+    for pattern in &trie.glob_patterns {
+        push_range(out, *pattern);
+    }
+    out.extend_from_slice(trie.strings.as_bytes());
+}
+
+/// reads a body front to back.
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], SnapshotError> {
+        if len > self.bytes.len() {
+            return Err(SnapshotError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(len);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    /// `count` records of `words` words each.
+    fn records(&mut self, count: usize, words: usize) -> Result<&'a [u8], SnapshotError> {
+        let len = count
+            .checked_mul(words * WORD)
+            .ok_or(SnapshotError::Truncated)?;
+        self.take(len)
+    }
+
+    fn count(&mut self) -> Result<usize, SnapshotError> {
+        Ok(word(self.take(WORD)?, 0) as usize)
+    }
+}
+
+/// a `FrozenTrie`, borrowed from a snapshot.
+#[derive(Debug)]
+struct SnapshotTrie<'a> {
+    nodes: &'a [u8],
+    tails: &'a [u8],
+    literal_edges: &'a [u8],
+    glob_edges: &'a [u8],
+    glob_patterns: &'a [u8],
+    strings: &'a str,
+    globs: Vec<Lazy<SegmentGlob>>,
+    #[cfg(feature = "std")]
+    glob_sets: Vec<Lazy<GlobSetMatcher>>,
+}
+
+impl<'a> SnapshotTrie<'a> {
+    fn read(reader: &mut Reader<'a>) -> Result<Self, SnapshotError> {
+        let nodes = reader.count()?;
+        let tails = reader.count()?;
+        let literal_edges = reader.count()?;
+        let glob_edges = reader.count()?;
+        let globs = reader.count()?;
+        let glob_sets = reader.count()?;
+        let strings = reader.count()?;
+
+        let trie = SnapshotTrie {
+            nodes: reader.records(nodes, NODE_WORDS)?,
+            tails: reader.records(tails, TAIL_WORDS)?,
+            literal_edges: reader.records(literal_edges, LITERAL_EDGE_WORDS)?,
+            glob_edges: reader.records(glob_edges, GLOB_EDGE_WORDS)?,
+            glob_patterns: reader.records(globs, GLOB_WORDS)?,
+            strings: core::str::from_utf8(reader.take(strings)?).map_err(|_| {
+                SnapshotError::Malformed {
+                    reason: "strings aren't utf-8",
+                }
+            })?,
+            globs: (0..globs).map(|_| Lazy::new()).collect(),
+            // every glob set belongs to a node, which bounds this by the
+            // input until it's validated.
+            #[cfg(feature = "std")]
+            glob_sets: (0..glob_sets.min(nodes)).map(|_| Lazy::new()).collect(),
+        };
+        trie.validate(glob_sets)?;
+        Ok(trie)
+    }
+
+    fn len(records: &[u8], words: usize) -> usize {
+        records.len() / (words * WORD)
+    }
+
+    /// check every index is in bounds, so evaluation never panics. globs are
+    /// parsed, but not compiled.
+    fn validate(&self, glob_sets: usize) -> Result<(), SnapshotError> {
+        let malformed = |reason| Err(SnapshotError::Malformed { reason });
+        let nodes = Self::len(self.nodes, NODE_WORDS);
+        let within = |range: Range<usize>, records: &[u8], words| {
+            range.start <= range.end && range.end <= Self::len(records, words)
+        };
+        let string = |records, idx| self.strings.get(range(records, idx)).is_some();
+
+        if nodes == 0 {
+            return malformed("missing root node");
+        }
+
+        let mut next_glob_set = 0;
+        for node in 0..nodes {
+            let base = node * NODE_WORDS;
+            if !within(
+                range(self.nodes, base),
+                self.literal_edges,
+                LITERAL_EDGE_WORDS,
+            ) || !within(
+                range(self.nodes, base + 2),
+                self.glob_edges,
+                GLOB_EDGE_WORDS,
+            ) || !within(range(self.nodes, base + 5), self.tails, TAIL_WORDS)
+            {
+                return malformed("node range out of bounds");
+            }
+            match word(self.nodes, base + 4) {
+                NO_GLOB_SET => {}
+                glob_set if glob_set as usize == next_glob_set => next_glob_set += 1,
+                _ => return malformed("glob sets out of order"),
+            }
+            if word(self.nodes, base + 7) > 1 {
+                return malformed("invalid terminal flag");
+            }
+        }
+        if next_glob_set != glob_sets {
+            return malformed("glob set count mismatch");
+        }
+
+        if !(0..Self::len(self.tails, TAIL_WORDS)).all(|idx| string(self.tails, idx * TAIL_WORDS)) {
+            return malformed("tail out of bounds");
+        }
+        for idx in 0..Self::len(self.literal_edges, LITERAL_EDGE_WORDS) {
+            let base = idx * LITERAL_EDGE_WORDS;
+            if !string(self.literal_edges, base)
+                || word(self.literal_edges, base + 2) as usize >= nodes
+            {
+                return malformed("literal edge out of bounds");
+            }
+        }
+        for idx in 0..Self::len(self.glob_edges, GLOB_EDGE_WORDS) {
+            let base = idx * GLOB_EDGE_WORDS;
+            if word(self.glob_edges, base) as usize >= self.globs.len()
+                || word(self.glob_edges, base + 1) as usize >= nodes
+            {
+                return malformed("glob edge out of bounds");
+            }
+        }
+        for glob in 0..self.globs.len() {
+            if !string(self.glob_patterns, glob * GLOB_WORDS) {
+                return malformed("glob pattern out of bounds");
+            }
+            if !SegmentGlob::is_valid(self.glob_pattern(glob as u32)) {
+                return malformed("invalid glob pattern");
+            }
+        }
+
+        Ok(())
+    }
+
+    fn glob_pattern(&self, glob: u32) -> &'a str {
+        &self.strings[range(self.glob_patterns, glob as usize * GLOB_WORDS)]
+    }
+}
+
+impl Arena for SnapshotTrie<'_> {
+    fn node(&self, node: u32) -> FrozenNode {
+        let base = node as usize * NODE_WORDS;
+        FrozenNode {
+            literals: IndexRange::new(range(self.nodes, base)),
+            globs: IndexRange::new(range(self.nodes, base + 2)),
+            #[cfg(feature = "std")]
+            glob_set: Some(word(self.nodes, base + 4)).filter(|glob_set| *glob_set != NO_GLOB_SET),
+            tail: IndexRange::new(range(self.nodes, base + 5)),
+            terminal: word(self.nodes, base + 7) == 1,
+        }
+    }
+
+    fn tail(&self, idx: usize) -> &str {
+        &self.strings[range(self.tails, idx * TAIL_WORDS)]
+    }
+
+    fn literal_edge(&self, idx: usize) -> (&str, u32) {
+        let base = idx * LITERAL_EDGE_WORDS;
+        (
+            &self.strings[range(self.literal_edges, base)],
+            word(self.literal_edges, base + 2),
+        )
+    }
+
+    fn glob_edge(&self, idx: usize) -> GlobEdge {
+        let base = idx * GLOB_EDGE_WORDS;
+        GlobEdge {
+            glob: word(self.glob_edges, base),
+            node: word(self.glob_edges, base + 1),
+        }
+    }
+
+    fn is_glob_match(&self, glob: u32, segment: &str) -> bool {
+        self.globs[glob as usize]
+            .get_or_init(|| {
+                SegmentGlob::new(self.glob_pattern(glob))
+                    .expect("glob patterns are validated on load")
+            })
+            .is_match(segment)
+    }
+
+    #[cfg(feature = "std")]
+    fn glob_set(&self, glob_set: u32, globs: IndexRange) -> &GlobSetMatcher {
+        self.glob_sets[glob_set as usize].get_or_init(|| {
+            GlobSetMatcher::new(
+                globs
+                    .as_range()
+                    .map(|idx| self.glob_pattern(self.glob_edge(idx).glob)),
+            )
+        })
+    }
+}
+
+/// a `FrozenAcm`, read in place from a snapshot written by
+/// `FrozenAcm::to_snapshot`.
+///
+/// loading checks the header, checksum and every index, but copies none of
+/// the arena and compiles no globs. each glob (and set of sibling globs) is
+/// compiled on first use, so a snapshot can be loaded straight out of a
+/// memory map at startup and warm up as requests arrive.
+///
+/// the checksum detects corruption, not tampering: only load snapshots from a
+/// trusted source.
+#[derive(Debug)]
+pub struct AcmSnapshot<'a> {
+    allow: SnapshotTrie<'a>,
+    deny: SnapshotTrie<'a>,
+}
+
+impl<'a> AcmSnapshot<'a> {
+    pub fn load(bytes: &'a [u8]) -> Result<Self, SnapshotError> {
+        let header = bytes.get(..HEADER_LEN).ok_or(SnapshotError::Truncated)?;
+        if header[..MAGIC.len()] != MAGIC {
+            return Err(SnapshotError::BadMagic);
+        }
+        let version = word(header, 2);
+        if version != SNAPSHOT_VERSION {
+            return Err(SnapshotError::UnsupportedVersion { found: version });
+        }
+
+        let body = &bytes[HEADER_LEN..];
+        let body_len = word(header, 3) as usize;
+        if body.len() < body_len {
+            return Err(SnapshotError::Truncated);
+        }
+        if body.len() > body_len {
+            return Err(SnapshotError::Malformed {
+                reason: "trailing bytes",
+            });
+        }
+
+        let expected = u64::from_le_bytes(header[16..].try_into().expect("a checksum is 8 bytes"));
+        let found = fnv1a(body);
+        if expected != found {
+            return Err(SnapshotError::ChecksumMismatch { expected, found });
+        }
+
+        let mut reader = Reader { bytes: body };
+        let allow = SnapshotTrie::read(&mut reader)?;
+        let deny = SnapshotTrie::read(&mut reader)?;
+        if !reader.bytes.is_empty() {
+            return Err(SnapshotError::Malformed {
+                reason: "trailing bytes",
+            });
+        }
+
+        Ok(AcmSnapshot { allow, deny })
+    }
+
+    pub fn enforce(&self, action: &str, resource_path: &str) -> bool {
+        let segments: SmallVec<[&str; STACK_CAPACITY]> = action
+            .split(ACTION_SEPARATOR)
+            .chain(resource_path.split(RESOURCE_SEPARATOR))
+            .collect();
+
+        !contains(&self.deny, &segments) && contains(&self.allow, &segments)
+    }
+
+    pub fn enforce_batch(&self, requests: &[(&str, &str)]) -> Vec<bool> {
+        let mut results = Vec::with_capacity(requests.len());
+        let mut segments: SmallVec<[&str; STACK_CAPACITY]> = SmallVec::new();
+
+        for (action, resource_path) in requests {
+            segments.clear();
+            segments.extend(
+                action
+                    .split(ACTION_SEPARATOR)
+                    .chain(resource_path.split(RESOURCE_SEPARATOR)),
+            );
+
+            let granted = !contains(&self.deny, &segments) && contains(&self.allow, &segments);
+            results.push(granted);
+        }
+
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+
+    use super::*;
+    use crate::Acm;
+
+    const REQUESTS: &[(&str, &str)] = &[
+        ("action:Get", "resource/path"),
+        ("action:Get", "other/path"),
+        ("action:Delete", "resource/sensitive"),
+        ("action:Delete", "resource/normal"),
+        ("identity:DeleteUser", "users/admin"),
+        ("identity:CreateUser", "users/team-a/amos"),
+        ("billing:GetInvoice", "accounts/36UeVtK7/invoices/2024-01"),
+        ("billing:PayInvoice", "accounts/36UeVtK7/invoices/2024-01"),
+        ("action:Get", "teams/team-c-ops"),
+        ("action:Get", "teams/ops-c"),
+        ("action:Get", "teams/team-f-ops"),
+        ("action:Get", "other/path/deeper"),
+        ("", ""),
+    ];
+
+    fn acm() -> Acm {
+        let mut acm = Acm::new();
+        acm.allow("action:Get", "resource/path");
+        acm.allow("action:*", "resource/*");
+        acm.allow("identity:*", "users/*");
+        acm.allow("identity:{Create,Update}User*", "users/team-?*/*");
+        acm.allow("billing:Get*", "accounts/*/invoices/*");
+        acm.allow("action:Get", "*/path/deeper");
+        acm.deny("action:Delete", "resource/sensitive");
+        acm.deny("identity:DeleteUser", "users/admin");
+        for team in ["a", "b", "c", "d", "e"] {
+            acm.allow("action:Get", &format!("teams/team-{team}-*"));
+            acm.allow("action:Get", &format!("teams/*-{team}"));
+        }
+        acm
+    }
+
+    /// recompute the checksum, after tampering with the body.
+    fn reseal(bytes: &mut [u8]) {
+        let checksum = fnv1a(&bytes[HEADER_LEN..]);
+        bytes[16..HEADER_LEN].copy_from_slice(&checksum.to_le_bytes());
+    }
+
+    #[test]
+    fn fnv1a_matches_reference_vectors() {
+        assert_eq!(fnv1a(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn snapshot_agrees_with_acm() {
+        let acm = acm();
+        let bytes = acm.freeze().to_snapshot();
+        let snapshot = AcmSnapshot::load(&bytes).unwrap();
+
+        for (action, resource) in REQUESTS {
+            assert_eq!(
+                snapshot.enforce(action, resource),
+                acm.enforce(action, resource),
+                "{action} {resource}"
+            );
+        }
+        assert_eq!(
+            snapshot.enforce_batch(REQUESTS),
+            acm.enforce_batch(REQUESTS)
+        );
+    }
+
+    #[test]
+    fn snapshot_compiles_globs_lazily() {
+        let bytes = acm().freeze().to_snapshot();
+        let snapshot = AcmSnapshot::load(&bytes).unwrap();
+        assert!(snapshot.allow.globs.iter().all(|glob| glob.get().is_none()));
+
+        assert!(snapshot.enforce("action:Get", "resource/path"));
+        let compiled = snapshot
+            .allow
+            .globs
+            .iter()
+            .filter(|glob| glob.get().is_some());
+        assert_eq!(compiled.count(), 1);
+    }
+
+    #[test]
+    fn snapshot_is_unaligned() {
+        let bytes = acm().freeze().to_snapshot();
+        let mut shifted = Vec::from([0]);
+        shifted.extend_from_slice(&bytes);
+
+        let snapshot = AcmSnapshot::load(&shifted[1..]).unwrap();
+        assert!(snapshot.enforce("action:Get", "teams/team-c-ops"));
+    }
+
+    #[test]
+    fn snapshot_of_empty_acm() {
+        let bytes = Acm::new().freeze().to_snapshot();
+        let snapshot = AcmSnapshot::load(&bytes).unwrap();
+        assert!(!snapshot.enforce("action:Get", "resource/path"));
+    }
+
+    #[test]
+    fn load_rejects_bad_headers() {
+        let bytes = acm().freeze().to_snapshot();
+
+        assert_eq!(
+            AcmSnapshot::load(&bytes[..HEADER_LEN - 1]).unwrap_err(),
+            SnapshotError::Truncated
+        );
+        assert_eq!(
+            AcmSnapshot::load(&bytes[..bytes.len() - 1]).unwrap_err(),
+            SnapshotError::Truncated
+        );
+
+        let mut bad_magic = bytes.clone();
+        bad_magic[0] = b'X';
+        assert_eq!(
+            AcmSnapshot::load(&bad_magic).unwrap_err(),
+            SnapshotError::BadMagic
+        );
+
+        let mut bad_version = bytes.clone();
+        bad_version[8] = 2;
+        assert_eq!(
+            AcmSnapshot::load(&bad_version).unwrap_err(),
+            SnapshotError::UnsupportedVersion { found: 2 }
+        );
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(matches!(
+            AcmSnapshot::load(&trailing).unwrap_err(),
+            SnapshotError::Malformed { .. }
+        ));
+    }
+
+    #[test]
+    fn load_rejects_corruption() {
+        let bytes = acm().freeze().to_snapshot();
+
+        let mut corrupt = bytes.clone();
+        *corrupt.last_mut().unwrap() ^= 1;
+        assert!(matches!(
+            AcmSnapshot::load(&corrupt).unwrap_err(),
+            SnapshotError::ChecksumMismatch { .. }
+        ));
+
+        // the root's literal edges, pointing past the end of the array
+        let mut out_of_bounds = bytes.clone();
+        let root_literals_end = HEADER_LEN + 7 * WORD + WORD;
+        out_of_bounds[root_literals_end..root_literals_end + WORD]
+            .copy_from_slice(&u32::MAX.to_le_bytes());
+        reseal(&mut out_of_bounds);
+        assert_eq!(
+            AcmSnapshot::load(&out_of_bounds).unwrap_err(),
+            SnapshotError::Malformed {
+                reason: "node range out of bounds"
+            }
+        );
+    }
+}
@@ -0,0 +1,720 @@
+use alloc::string::String;
+
+use thiserror::Error;
+
+/// a rule segment which contains `*`, but isn't a valid glob.
+#[derive(Debug, Clone, Error, PartialEq, Eq)]
+#[error("invalid glob pattern {pattern:?}")]
+pub struct InvalidGlob {
+    pattern: String,
+}
+
+impl InvalidGlob {
+    pub(crate) fn new(pattern: &str) -> Self {
+        InvalidGlob {
+            pattern: pattern.into(),
+        }
+    }
+
+    /// the offending segment.
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+}
+
+/// a compiled glob, matched against a single action or resource segment.
+///
+/// with the `std` feature this is `globset`. without it, a small matcher with
+/// the same syntax and semantics (`?`, `*`, `**`, `[...]`, `{a,b}` and `\`
+/// escapes, matched byte-wise) stands in, since `globset` needs `std`.
+#[derive(Debug)]
+pub(crate) struct SegmentGlob {
+    #[cfg(feature = "std")]
+    matcher: globset::GlobMatcher,
+    #[cfg(not(feature = "std"))]
+    matcher: fallback::Nfa,
+}
+
+impl SegmentGlob {
+    /// compile `pattern`, or `None` if it isn't a valid glob.
+    pub(crate) fn new(pattern: &str) -> Option<Self> {
+        #[cfg(feature = "std")]
+        let matcher = globset::Glob::new(pattern).ok()?.compile_matcher();
+        #[cfg(not(feature = "std"))]
+        let matcher = fallback::Nfa::new(pattern)?;
+
+        Some(SegmentGlob { matcher })
+    }
+
+    /// whether `pattern` is a valid glob, without compiling it.
+    pub(crate) fn is_valid(pattern: &str) -> bool {
+        #[cfg(feature = "std")]
+        return globset::Glob::new(pattern).is_ok();
+        #[cfg(not(feature = "std"))]
+        return fallback::Nfa::new(pattern).is_some();
+    }
+
+    /// whether `segment` matches the glob.
+    pub(crate) fn is_match(&self, segment: &str) -> bool {
+        self.matcher.is_match(segment)
+    }
+}
+
+/// sibling globs, compiled into a single `globset::GlobSet` so one call finds
+/// every sibling which matches a segment.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub(crate) struct GlobSetMatcher {
+    set: globset::GlobSet,
+}
+
+#[cfg(feature = "std")]
+std::thread_local! {
+    // `GlobSet` reports matches into a `Vec`, reusing one per thread keeps
+    // matching allocation-free once warm.
+    static MATCHES: core::cell::RefCell<alloc::vec::Vec<usize>> =
+        const { core::cell::RefCell::new(alloc::vec::Vec::new()) };
+}
+
+#[cfg(feature = "std")]
+impl GlobSetMatcher {
+    /// compile `patterns`, which must all be valid globs.
+    pub(crate) fn new<'p>(patterns: impl IntoIterator<Item = &'p str>) -> Self {
+        let mut builder = globset::GlobSetBuilder::new();
+        for pattern in patterns {
+            builder
+                .add(globset::Glob::new(pattern).expect("glob patterns are validated on insert"));
+        }
+        let set = builder
+            .build()
+            .expect("valid globs always compile into a set");
+        GlobSetMatcher { set }
+    }
+
+    /// call `f` with the index of every pattern matching `segment`, in
+    /// ascending order.
+    pub(crate) fn for_each_match(&self, segment: &str, mut f: impl FnMut(usize)) {
+        let candidate = globset::Candidate::new(segment);
+        MATCHES.with_borrow_mut(|matches| {
+            self.set.matches_candidate_into(&candidate, matches);
+            matches.iter().for_each(|idx| f(*idx));
+        });
+    }
+}
+
+/// a `GlobSetMatcher` for a node whose glob children can still change.
+///
+/// the set is compiled lazily, on the first match after it's invalidated, so
+/// inserting many siblings only pays for one compilation.
+#[cfg(feature = "std")]
+#[derive(Debug, Default)]
+pub(crate) struct SegmentGlobSet {
+    compiled: std::sync::OnceLock<GlobSetMatcher>,
+}
+
+#[cfg(feature = "std")]
+impl SegmentGlobSet {
+    /// forget the compiled set, the siblings have changed.
+    pub(crate) fn invalidate(&mut self) {
+        self.compiled.take();
+    }
+
+    /// the compiled set of `patterns`, compiling it if needed. `patterns`
+    /// must be the same globs, in the same order, until the set is
+    /// invalidated.
+    pub(crate) fn compiled<'p>(
+        &self,
+        patterns: impl IntoIterator<Item = &'p str>,
+    ) -> &GlobSetMatcher {
+        self.compiled.get_or_init(|| GlobSetMatcher::new(patterns))
+    }
+}
+
+/// globs as byte-wise atoms: the matcher without `std`, and how globs are
+/// reasoned about (e.g. whether two overlap) with it.
+pub(crate) mod fallback {
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::iter::Peekable;
+    use core::str::Chars;
+
+    /// the path separator, which `**` treats specially.
+    const SEPARATOR: char = '/';
+
+    /// a parsed glob token, mirroring `globset`'s parser.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    enum Token {
+        Literal(char),
+        Any,
+        ZeroOrMore,
+        RecursivePrefix,
+        RecursiveSuffix,
+        RecursiveZeroOrMore,
+        Class {
+            negated: bool,
+            ranges: Vec<(char, char)>,
+        },
+        Alternates(Vec<Vec<Token>>),
+    }
+
+    /// a single byte-wise matching unit.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(crate) enum Atom {
+        Byte(u8),
+        Any,
+        Star,
+        Class { negated: bool, items: Vec<(u8, u8)> },
+    }
+
+    impl Atom {
+        /// whether the atom consumes `byte`. `Star` consumes any number of
+        /// bytes, so is handled by the caller.
+        pub(crate) fn matches(&self, byte: u8) -> bool {
+            match self {
+                Atom::Byte(b) => *b == byte,
+                Atom::Any => true,
+                Atom::Star => false,
+                Atom::Class { negated, items } => {
+                    items.iter().any(|(lo, hi)| (*lo..=*hi).contains(&byte)) != *negated
+                }
+            }
+        }
+    }
+
+    /// whether `tokens` would translate to an empty regex in `globset`, which
+    /// drops such alternate branches.
+    fn is_empty(tokens: &[Token]) -> bool {
+        tokens.iter().all(|token| match token {
+            Token::Alternates(branches) => branches.iter().all(|branch| is_empty(branch)),
+            _ => false,
+        })
+    }
+
+    /// a state of an `Nfa`.
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub(crate) enum State {
+        /// consume one byte the atom matches, then move to the next state. a
+        /// `Star` consumes any number of bytes, staying put, before moving
+        /// on.
+        Atom(Atom, usize),
+        /// move to any of the states, consuming nothing.
+        Split(Vec<usize>),
+        Accept,
+    }
+
+    /// a glob as a nondeterministic automaton over atoms. alternates aren't
+    /// expanded, so it's linear in the size of the pattern.
+    ///
+    /// it's the matcher without `std`, which tracks every state at once, so
+    /// matching is linear in the candidate too. globs of up to
+    /// `INLINE_STATES` states are matched without allocating.
+    #[derive(Debug)]
+    pub(crate) struct Nfa {
+        states: Vec<State>,
+        start: usize,
+    }
+
+    impl Nfa {
+        const ACCEPT: usize = 0;
+
+        /// compile `pattern`, or `None` if it isn't a valid glob.
+        pub(crate) fn new(pattern: &str) -> Option<Self> {
+            let tokens = Parser::parse(pattern)?;
+            let mut nfa = Nfa {
+                states: vec![State::Accept],
+                start: Self::ACCEPT,
+            };
+
+            // the entire glob being `**` matches everything.
+            nfa.start = if tokens == [Token::RecursivePrefix] {
+                nfa.push(State::Atom(Atom::Star, Self::ACCEPT))
+            } else {
+                nfa.build(&tokens, Self::ACCEPT)?
+            };
+            Some(nfa)
+        }
+
+        pub(crate) fn start(&self) -> usize {
+            self.start
+        }
+
+        pub(crate) fn state(&self, idx: usize) -> &State {
+            &self.states[idx]
+        }
+
+        pub(crate) fn len(&self) -> usize {
+            self.states.len()
+        }
+
+        fn push(&mut self, state: State) -> usize {
+            self.states.push(state);
+            self.states.len() - 1
+        }
+
+        fn byte(&mut self, byte: u8, next: usize) -> usize {
+            self.push(State::Atom(Atom::Byte(byte), next))
+        }
+
+        /// the states matching `tokens`, built back to front since each
+        /// needs to know what follows it. returns the first state.
+        fn build(&mut self, tokens: &[Token], mut next: usize) -> Option<usize> {
+            for token in tokens.iter().rev() {
+                next = match token {
+                    Token::Literal(c) => c
+                        .encode_utf8(&mut [0; 4])
+                        .bytes()
+                        .rev()
+                        .fold(next, |next, byte| self.byte(byte, next)),
+                    Token::Any => self.push(State::Atom(Atom::Any, next)),
+                    Token::ZeroOrMore => self.push(State::Atom(Atom::Star, next)),
+                    // nothing, `/`, or `*/`
+                    Token::RecursivePrefix => {
+                        let slash = self.byte(b'/', next);
+                        let star = self.push(State::Atom(Atom::Star, slash));
+                        self.push(State::Split(vec![next, slash, star]))
+                    }
+                    // `/*`
+                    Token::RecursiveSuffix => {
+                        let star = self.push(State::Atom(Atom::Star, next));
+                        self.byte(b'/', star)
+                    }
+                    // `/`, or `/*/`
+                    Token::RecursiveZeroOrMore => {
+                        let last = self.byte(b'/', next);
+                        let star = self.push(State::Atom(Atom::Star, last));
+                        let first = self.byte(b'/', star);
+                        self.push(State::Split(vec![last, first]))
+                    }
+                    Token::Class { negated, ranges } => {
+                        let atom = Atom::Class {
+                            negated: *negated,
+                            items: class_items(ranges)?,
+                        };
+                        self.push(State::Atom(atom, next))
+                    }
+                    Token::Alternates(branches) => {
+                        let mut starts = Vec::new();
+                        for branch in branches.iter().filter(|branch| !is_empty(branch)) {
+                            starts.push(self.build(branch, next)?);
+                        }
+                        if starts.is_empty() {
+                            continue;
+                        }
+                        self.push(State::Split(starts))
+                    }
+                };
+            }
+            Some(next)
+        }
+
+        /// whether `candidate` matches.
+        #[cfg(any(not(feature = "std"), test))]
+        pub(crate) fn is_match(&self, candidate: &str) -> bool {
+            let words = self.len().div_ceil(u64::BITS as usize);
+            if words <= INLINE_WORDS {
+                self.simulate(
+                    candidate.as_bytes(),
+                    &mut [0; 2 * INLINE_WORDS][..2 * words],
+                )
+            } else {
+                self.simulate(candidate.as_bytes(), &mut vec![0; 2 * words])
+            }
+        }
+
+        /// run the automaton over `candidate`, with `scratch` split into the
+        /// current and next sets of states (one bit each).
+        #[cfg(any(not(feature = "std"), test))]
+        fn simulate(&self, candidate: &[u8], scratch: &mut [u64]) -> bool {
+            let (mut current, mut next) = scratch.split_at_mut(scratch.len() / 2);
+            self.close(current, self.start);
+            for &byte in candidate {
+                next.fill(0);
+                for (word_idx, word) in current.iter().enumerate() {
+                    let mut word = *word;
+                    while word != 0 {
+                        let idx = word_idx * u64::BITS as usize + word.trailing_zeros() as usize;
+                        word &= word - 1;
+                        match &self.states[idx] {
+                            State::Atom(Atom::Star, _) => self.close(next, idx),
+                            State::Atom(atom, to) if atom.matches(byte) => self.close(next, *to),
+                            _ => {}
+                        }
+                    }
+                }
+                if next.iter().all(|word| *word == 0) {
+                    return false;
+                }
+                core::mem::swap(&mut current, &mut next);
+            }
+            is_set(current, Self::ACCEPT)
+        }
+
+        /// add `idx`, and every state reachable from it without consuming, to
+        /// `set`.
+        #[cfg(any(not(feature = "std"), test))]
+        fn close(&self, set: &mut [u64], idx: usize) {
+            if is_set(set, idx) {
+                return;
+            }
+            set[idx / u64::BITS as usize] |= 1 << (idx % u64::BITS as usize);
+            match &self.states[idx] {
+                State::Atom(Atom::Star, next) => self.close(set, *next),
+                State::Split(nexts) => nexts.iter().for_each(|next| self.close(set, *next)),
+                _ => {}
+            }
+        }
+    }
+
+    /// the most states an `Nfa` may have and still be matched without
+    /// allocating, i.e. with its sets of states on the stack.
+    #[cfg(any(not(feature = "std"), test))]
+    pub(crate) const INLINE_STATES: usize = 512;
+
+    #[cfg(any(not(feature = "std"), test))]
+    const INLINE_WORDS: usize = INLINE_STATES / u64::BITS as usize;
+
+    /// whether state `idx` is in `set`.
+    #[cfg(any(not(feature = "std"), test))]
+    fn is_set(set: &[u64], idx: usize) -> bool {
+        set[idx / u64::BITS as usize] & (1 << (idx % u64::BITS as usize)) != 0
+    }
+
+    /// translate a character class into byte ranges, as `globset`'s
+    /// non-unicode regex does: a multi-byte character contributes each of its
+    /// bytes, and a range spans the last byte of its start to the first byte
+    /// of its end.
+    fn class_items(ranges: &[(char, char)]) -> Option<Vec<(u8, u8)>> {
+        let mut items = Vec::new();
+        for (start, end) in ranges {
+            let (mut start_buf, mut end_buf) = ([0; 4], [0; 4]);
+            let start_bytes = start.encode_utf8(&mut start_buf).as_bytes();
+            if start == end {
+                items.extend(start_bytes.iter().map(|b| (*b, *b)));
+                continue;
+            }
+
+            let end_bytes = end.encode_utf8(&mut end_buf).as_bytes();
+            let (last, leading) = start_bytes.split_last()?;
+            let (first, trailing) = end_bytes.split_first()?;
+            if last > first {
+                return None;
+            }
+            items.extend(leading.iter().map(|b| (*b, *b)));
+            items.push((*last, *first));
+            items.extend(trailing.iter().map(|b| (*b, *b)));
+        }
+        Some(items)
+    }
+
+    /// a port of `globset`'s parser, with its default options.
+    struct Parser<'a> {
+        alternates_stack: Vec<usize>,
+        branches: Vec<Vec<Token>>,
+        chars: Peekable<Chars<'a>>,
+        prev: Option<char>,
+        cur: Option<char>,
+    }
+
+    impl<'a> Parser<'a> {
+        fn parse(pattern: &'a str) -> Option<Vec<Token>> {
+            let mut parser = Parser {
+                alternates_stack: Vec::new(),
+                branches: vec![Vec::new()],
+                chars: pattern.chars().peekable(),
+                prev: None,
+                cur: None,
+            };
+
+            while let Some(c) = parser.bump() {
+                match c {
+                    '?' => parser.push_token(Token::Any)?,
+                    '*' => parser.parse_star()?,
+                    '[' => parser.parse_class()?,
+                    '{' => {
+                        parser.alternates_stack.push(parser.branches.len());
+                        parser.branches.push(Vec::new());
+                    }
+                    '}' => {
+                        let start = parser.alternates_stack.pop()?;
+                        let alternates = parser.branches.drain(start..).collect();
+                        parser.push_token(Token::Alternates(alternates))?;
+                    }
+                    ',' if !parser.alternates_stack.is_empty() => {
+                        parser.branches.push(Vec::new());
+                    }
+                    '\\' => {
+                        let escaped = parser.bump()?;
+                        parser.push_token(Token::Literal(escaped))?;
+                    }
+                    c => parser.push_token(Token::Literal(c))?,
+                }
+            }
+
+            // unclosed alternates
+            if parser.branches.len() != 1 {
+                return None;
+            }
+            parser.branches.pop()
+        }
+
+        fn bump(&mut self) -> Option<char> {
+            self.prev = self.cur;
+            self.cur = self.chars.next();
+            self.cur
+        }
+
+        fn push_token(&mut self, token: Token) -> Option<()> {
+            self.branches.last_mut()?.push(token);
+            Some(())
+        }
+
+        fn have_tokens(&self) -> Option<bool> {
+            Some(!self.branches.last()?.is_empty())
+        }
+
+        fn parse_star(&mut self) -> Option<()> {
+            let prev = self.prev;
+            if self.chars.peek() != Some(&'*') {
+                return self.push_token(Token::ZeroOrMore);
+            }
+            self.bump();
+
+            if !self.have_tokens()? {
+                if self.chars.peek().is_some_and(|c| *c != SEPARATOR) {
+                    self.push_token(Token::ZeroOrMore)?;
+                    return self.push_token(Token::ZeroOrMore);
+                }
+                self.push_token(Token::RecursivePrefix)?;
+                self.bump();
+                return Some(());
+            }
+
+            if prev != Some(SEPARATOR)
+                && (self.branches.len() <= 1 || (prev != Some(',') && prev != Some('{')))
+            {
+                self.push_token(Token::ZeroOrMore)?;
+                return self.push_token(Token::ZeroOrMore);
+            }
+
+            let is_suffix = match self.chars.peek() {
+                None => true,
+                Some(',') | Some('}') if self.branches.len() >= 2 => true,
+                Some(&SEPARATOR) => {
+                    self.bump();
+                    false
+                }
+                _ => {
+                    self.push_token(Token::ZeroOrMore)?;
+                    return self.push_token(Token::ZeroOrMore);
+                }
+            };
+
+            let token = match self.branches.last_mut()?.pop()? {
+                Token::RecursivePrefix => Token::RecursivePrefix,
+                Token::RecursiveSuffix => Token::RecursiveSuffix,
+                _ if is_suffix => Token::RecursiveSuffix,
+                _ => Token::RecursiveZeroOrMore,
+            };
+            self.push_token(token)
+        }
+
+        fn parse_class(&mut self) -> Option<()> {
+            let mut ranges: Vec<(char, char)> = Vec::new();
+            let negated = matches!(self.chars.peek(), Some('!') | Some('^'));
+            if negated {
+                self.bump();
+            }
+
+            let mut first = true;
+            let mut in_range = false;
+            loop {
+                // unclosed class
+                let c = self.bump()?;
+                match c {
+                    ']' if first => ranges.push((']', ']')),
+                    ']' => break,
+                    '-' if first => ranges.push(('-', '-')),
+                    '-' if in_range => {
+                        let range = ranges.last_mut()?;
+                        range.1 = '-';
+                        if range.1 < range.0 {
+                            return None;
+                        }
+                        in_range = false;
+                    }
+                    '-' => in_range = true,
+                    c => {
+                        if in_range {
+                            let range = ranges.last_mut()?;
+                            range.1 = c;
+                            if range.1 < range.0 {
+                                return None;
+                            }
+                        } else {
+                            ranges.push((c, c));
+                        }
+                        in_range = false;
+                    }
+                }
+                first = false;
+            }
+            if in_range {
+                ranges.push(('-', '-'));
+            }
+
+            self.push_token(Token::Class { negated, ranges })
+        }
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod tests {
+    use alloc::string::String;
+
+    use super::SegmentGlob;
+    use super::fallback::{INLINE_STATES, Nfa};
+
+    const PATTERNS: &[&str] = &[
+        "*",
+        "**",
+        "***",
+        "a*",
+        "*a",
+        "a*b*c",
+        "team-*-admin",
+        "Get*",
+        "?et*",
+        "a**",
+        "**a",
+        "a/**",
+        "**/a",
+        "a/**/b",
+        "**/**",
+        "a/**/**",
+        "[abc]*",
+        "[!abc]*",
+        "[^abc]*",
+        "[a-c]x*",
+        "[]a]*",
+        "[-a]*",
+        "[a-]*",
+        "[z-a]*",
+        "[a-c-e]*",
+        "[é]*",
+        "[a-é]*",
+        "[é-ü]*",
+        "{a,b}*",
+        "{a,{b,c}}*",
+        "{,a}*",
+        "{}*",
+        "{{},a}*",
+        "x{**,y}",
+        "x/{**,y}",
+        "{a,b",
+        "a,b}*",
+        "{a,b}}*",
+        "a,b*",
+        "\\**",
+        "*\\",
+        "[",
+        "[*",
+        "[!]*",
+        "é*",
+        "*é",
+        "?*",
+        "??*",
+        "\u{1F980}*",
+    ];
+
+    const CANDIDATES: &[&str] = &[
+        "",
+        "a",
+        "b",
+        "c",
+        "x",
+        "ab",
+        "abc",
+        "aXbYc",
+        "team-a-admin",
+        "team--admin",
+        "Get",
+        "GetObject",
+        "get",
+        "a/b",
+        "a/x/b",
+        "/a",
+        "a/",
+        "x/",
+        "x/y",
+        "xy",
+        "]",
+        "-",
+        "*",
+        "*x",
+        "é",
+        "éa",
+        "ü",
+        "e",
+        "ea",
+        ",a",
+        "a,b",
+        "\u{1F980}",
+        "\u{1F980}!",
+    ];
+
+    #[test]
+    fn fallback_agrees_with_globset() {
+        for pattern in PATTERNS {
+            let globset = globset::Glob::new(pattern).map(|g| g.compile_matcher());
+            let nfa = Nfa::new(pattern);
+            assert_eq!(globset.is_ok(), nfa.is_some(), "validity of {pattern:?}");
+
+            if let (Ok(globset), Some(nfa)) = (globset, nfa) {
+                for candidate in CANDIDATES {
+                    assert_eq!(
+                        globset.is_match(candidate),
+                        nfa.is_match(candidate),
+                        "{pattern:?} against {candidate:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn alternates_are_not_expanded() {
+        // 4^64 expansions, which the automaton holds as 4 * 64 branches
+        let pattern: String = "{a,b,c,d}".repeat(64) + "*";
+        assert!(SegmentGlob::is_valid(&pattern));
+        let nfa = Nfa::new(&pattern).unwrap();
+        assert!(nfa.len() <= INLINE_STATES);
+
+        let candidate = "abcd".repeat(16);
+        let globset = SegmentGlob::new(&pattern).unwrap();
+        assert!(globset.is_match(&candidate));
+        assert!(nfa.is_match(&candidate));
+        assert!(!nfa.is_match(&candidate[1..]));
+    }
+
+    #[test]
+    fn large_globs_agree_with_globset() {
+        // too many states to match on the stack
+        let pattern = "x".repeat(INLINE_STATES) + "*{a,b}";
+        let nfa = Nfa::new(&pattern).unwrap();
+        assert!(nfa.len() > INLINE_STATES);
+
+        let globset = SegmentGlob::new(&pattern).unwrap();
+        for candidate in [
+            "x".repeat(INLINE_STATES) + "a",
+            "x".repeat(INLINE_STATES) + "yb",
+            "x".repeat(INLINE_STATES) + "c",
+            "x".repeat(INLINE_STATES - 1) + "a",
+        ] {
+            assert_eq!(
+                globset.is_match(&candidate),
+                nfa.is_match(&candidate),
+                "{candidate:?}"
+            );
+        }
+    }
+}
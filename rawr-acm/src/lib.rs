@@ -1,4 +1,61 @@
+//! rawr-acm
+//!
+//! an `Acm` can be frozen into a `FrozenAcm` once its rules are loaded, a
+//! read-only form laid out in contiguous arrays for large rule sets. a
+//! `FrozenAcm` can be written out as a versioned, checksummed snapshot, which
+//! `AcmSnapshot` reads back in place (e.g. from a memory map) without
+//! rebuilding the trie or compiling globs up front.
+//!
+//! it can also be compiled into a `CompiledAcm`, a deterministic automaton
+//! with one transition per request segment, so worst-case evaluation cost is
+//! bounded by the request rather than by how many glob rules overlap. an
+//! automaton is capped at `DFA_MAX_STATES` states and `DFA_MAX_STATE_GLOBS`
+//! globs per state; rule sets which would exceed either fall back to the
+//! trie.
+//!
+//! beyond point checks via `Acm::enforce`, `Acm::query` lists the allow
+//! rules which may grant requests under a resource prefix, e.g. for admin
+//! UIs. `Acm::analyze` reports duplicate rules, rules subsumed by broader
+//! ones, and allow rules which a deny rule cancels entirely. `overlap`
+//! decides whether two rule patterns match a common request, and finds one.
+//!
+//! matrices can be combined: `Acm::union` and `Acm::intersect` (and
+//! `CombinedAcm` generally) evaluate several matrices under a `Combining`
+//! rule, while `Acm::merge_rules` and `Acm::merge_denies` merge rules into one
+//! matrix, where a deny from either overrides allows from both.
+//!
+//! for services which update rules while serving requests, a `SharedAcm`
+//! publishes frozen snapshots by atomic pointer swap, so readers never wait
+//! on writers.
+//!
+//! features:
+//! - `std` (default): glob segments are matched by `globset`. without it the
+//!   crate is `no_std`, only needs `alloc`, and uses a built-in glob matcher
+//!   with the same syntax. `SharedAcm` requires `std`.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
 mod acm;
+mod analysis;
+mod combine;
+mod dfa;
+mod frozen;
+mod glob;
+mod overlap;
+mod pattern;
+mod query;
+#[cfg(feature = "std")]
+mod shared;
 mod trie;
 
 pub use acm::Acm;
+pub use analysis::{Effect, Finding, Rule};
+pub use combine::{CombinedAcm, Combining};
+pub use dfa::{CompiledAcm, DFA_MAX_STATE_GLOBS, DFA_MAX_STATES};
+pub use frozen::{AcmSnapshot, FrozenAcm, SNAPSHOT_VERSION, SnapshotError};
+pub use glob::InvalidGlob;
+pub use overlap::overlap;
+pub use query::Grant;
+#[cfg(feature = "std")]
+pub use shared::{SharedAcm, Transaction};
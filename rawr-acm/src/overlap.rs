@@ -0,0 +1,389 @@
+use alloc::collections::VecDeque;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::acm::{ACTION_SEPARATOR, RESOURCE_SEPARATOR};
+use crate::glob::SegmentGlob;
+use crate::glob::fallback::{Atom, Nfa, State};
+use crate::pattern::{ANY_SEGMENT, Pattern};
+
+/// the bytes a witness is built from first, where a glob leaves the choice
+/// open, so witnesses are readable where they can be.
+const WITNESS_BYTES: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-_. !\"#$%&'()*+,/:;<=>?@[\\]^`{|}~";
+
+/// a request matched by both `a` and `b`, each an `(action, resource_path)`
+/// pattern as passed to `Acm::allow` or `Acm::deny`, or `None` if no request
+/// could match both.
+///
+/// patterns are compared segment by segment with the same semantics as the
+/// `Acm`: a segment containing `*` is a glob (with `?`, `[...]` classes,
+/// `{a,b}` alternates and `\` escapes), and any other segment is a literal.
+/// the witness is split as `a` is, e.g. an allow and a deny overlap if
+/// `Acm::enforce` on the witness would consult both.
+///
+/// # panics
+///
+/// if either pattern contains an invalid glob, as `Acm::allow` does.
+pub fn overlap(a: (&str, &str), b: (&str, &str)) -> Option<(String, String)> {
+    let (a, action_len) = compile(a);
+    let (b, _) = compile(b);
+    if a.len() != b.len() {
+        return None;
+    }
+
+    let mut segments = Vec::with_capacity(a.len());
+    for (idx, (a, b)) in a.iter().zip(&b).enumerate() {
+        let separator = if idx < action_len {
+            ACTION_SEPARATOR
+        } else {
+            RESOURCE_SEPARATOR
+        };
+        segments.push(segment_witness(pattern(a), pattern(b), separator)?);
+    }
+
+    let resource = segments.split_off(action_len);
+    Some((
+        segments.join(ACTION_SEPARATOR),
+        resource.join(RESOURCE_SEPARATOR),
+    ))
+}
+
+/// the segments of a rule, each with its glob if it has one, and how many of
+/// them are the action.
+fn compile<'r>(
+    (action, resource_path): (&'r str, &'r str),
+) -> (Vec<(&'r str, Option<SegmentGlob>)>, usize) {
+    let segments: Vec<_> = action
+        .split(ACTION_SEPARATOR)
+        .chain(resource_path.split(RESOURCE_SEPARATOR))
+        .map(|segment| {
+            let glob = segment
+                .contains(ANY_SEGMENT)
+                .then(|| SegmentGlob::new(segment).expect("invalid glob pattern"));
+            (segment, glob)
+        })
+        .collect();
+    (segments, action.split(ACTION_SEPARATOR).count())
+}
+
+fn pattern<'p>((raw, glob): &'p (&'p str, Option<SegmentGlob>)) -> Pattern<'p> {
+    match glob {
+        Some(glob) => Pattern::Glob(raw, glob),
+        None => Pattern::Literal(raw),
+    }
+}
+
+/// a segment matched by both `a` and `b`, which doesn't contain `separator`
+/// (and so would be split the same way in a request).
+fn segment_witness(a: Pattern<'_>, b: Pattern<'_>, separator: &str) -> Option<String> {
+    let witness = match (a, b) {
+        (Pattern::Literal(a), Pattern::Literal(b)) => (a == b).then(|| a.to_string()),
+        (Pattern::Glob(_, glob), Pattern::Literal(literal))
+        | (Pattern::Literal(literal), Pattern::Glob(_, glob)) => {
+            glob.is_match(literal).then(|| literal.to_string())
+        }
+        (Pattern::Glob(a, _), Pattern::Glob(b, _)) => glob_witness(a, b, separator.as_bytes()),
+    }?;
+    (!witness.contains(separator)).then_some(witness)
+}
+
+/// a string matched by both globs without any `separator` byte.
+pub(crate) fn glob_witness(a: &str, b: &str, separator: &[u8]) -> Option<String> {
+    let a = Nfa::new(a).expect("glob patterns are validated on insert");
+    let b = Nfa::new(b).expect("glob patterns are validated on insert");
+    let witness = Product {
+        a: &a,
+        b: &b,
+        separator,
+    }
+    .witness()?;
+    String::from_utf8(witness).ok()
+}
+
+/// where a witness is within a utf-8 character: how many continuation bytes
+/// are still expected, and the range the next one must be in. the first is
+/// between characters.
+const UTF8_STATES: [(u8, u8, u8); 8] = [
+    (0, 0, 0),
+    (1, 0x80, 0xBF),
+    (2, 0x80, 0xBF),
+    (3, 0x80, 0xBF),
+    // after 0xE0, 0xED, 0xF0 and 0xF4, which rule out overlong encodings,
+    // surrogates and code points past U+10FFFF.
+    (2, 0xA0, 0xBF),
+    (2, 0x80, 0x9F),
+    (3, 0x90, 0xBF),
+    (3, 0x80, 0x8F),
+];
+
+/// the utf-8 state after `byte`, or `None` if it can't come next in valid
+/// utf-8.
+fn utf8_next(state: usize, byte: u8) -> Option<usize> {
+    let (remaining, lo, hi) = UTF8_STATES[state];
+    if remaining > 0 {
+        return (lo..=hi)
+            .contains(&byte)
+            .then_some(usize::from(remaining) - 1);
+    }
+    match byte {
+        0x00..=0x7F => Some(0),
+        0xC2..=0xDF => Some(1),
+        0xE0 => Some(4),
+        0xE1..=0xEC | 0xEE..=0xEF => Some(2),
+        0xED => Some(5),
+        0xF0 => Some(6),
+        0xF1..=0xF3 => Some(3),
+        0xF4 => Some(7),
+        _ => None,
+    }
+}
+
+/// the product of two globs' automata, searched breadth-first for a string
+/// both accept. each state is a state of either automaton, and of a utf-8
+/// decoder so the witness is always valid utf-8.
+struct Product<'n> {
+    a: &'n Nfa,
+    b: &'n Nfa,
+    separator: &'n [u8],
+}
+
+impl Product<'_> {
+    fn index(&self, (a, b, utf8): (usize, usize, usize)) -> usize {
+        (a * self.b.len() + b) * UTF8_STATES.len() + utf8
+    }
+
+    fn witness(&self) -> Option<Vec<u8>> {
+        let start = (self.a.start(), self.b.start(), 0);
+        // how each state was first reached: the previous state, and the byte
+        // consumed (none for moves which consume nothing).
+        let mut reached: Vec<Option<(usize, Option<u8>)>> =
+            vec![None; self.a.len() * self.b.len() * UTF8_STATES.len()];
+        let mut queue = VecDeque::from([start]);
+        reached[self.index(start)] = Some((self.index(start), None));
+
+        let mut moves = Vec::new();
+        while let Some(current) = queue.pop_front() {
+            let (a, b, utf8) = current;
+            if (self.a.state(a), self.b.state(b), utf8) == (&State::Accept, &State::Accept, 0) {
+                return Some(self.trace(&reached, self.index(current)));
+            }
+
+            moves.clear();
+            self.moves(current, &mut moves);
+            for (next, byte) in moves.drain(..) {
+                let idx = self.index(next);
+                if reached[idx].is_none() {
+                    reached[idx] = Some((self.index(current), byte));
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    /// every state reachable from `(a, b, utf8)` in one move, and the byte it
+    /// consumes, if any.
+    fn moves(
+        &self,
+        (a, b, utf8): (usize, usize, usize),
+        moves: &mut Vec<((usize, usize, usize), Option<u8>)>,
+    ) {
+        for next in epsilon(self.a.state(a)) {
+            moves.push(((*next, b, utf8), None));
+        }
+        for next in epsilon(self.b.state(b)) {
+            moves.push(((a, *next, utf8), None));
+        }
+
+        let (Some(next_a), Some(next_b)) = (consumed(self.a, a), consumed(self.b, b)) else {
+            return;
+        };
+        // which byte doesn't matter to either automaton, only to the utf-8
+        // decoder, so take the first (most readable) byte for each of its
+        // states.
+        let forced = [self.a.state(a), self.b.state(b)]
+            .into_iter()
+            .find_map(|state| match state {
+                State::Atom(Atom::Byte(byte), _) => Some(*byte),
+                _ => None,
+            });
+        let mut seen = [false; UTF8_STATES.len()];
+        let mut consume = |byte: u8| {
+            if self.separator.contains(&byte)
+                || !consumes(self.a.state(a), byte)
+                || !consumes(self.b.state(b), byte)
+            {
+                return;
+            }
+            if let Some(next_utf8) = utf8_next(utf8, byte)
+                && !core::mem::replace(&mut seen[next_utf8], true)
+            {
+                moves.push(((next_a, next_b, next_utf8), Some(byte)));
+            }
+        };
+        match forced {
+            Some(byte) => consume(byte),
+            None => WITNESS_BYTES
+                .iter()
+                .copied()
+                .chain(0..=u8::MAX)
+                .for_each(consume),
+        }
+    }
+
+    /// the bytes consumed on the way to `end`.
+    fn trace(&self, reached: &[Option<(usize, Option<u8>)>], end: usize) -> Vec<u8> {
+        let mut witness = Vec::new();
+        let mut current = end;
+        loop {
+            let (previous, byte) = reached[current].expect("a reached state");
+            if previous == current {
+                break;
+            }
+            witness.extend(byte);
+            current = previous;
+        }
+        witness.reverse();
+        witness
+    }
+}
+
+/// the states reachable from `state` without consuming anything.
+fn epsilon(state: &State) -> &[usize] {
+    match state {
+        State::Atom(Atom::Star, next) => core::slice::from_ref(next),
+        State::Split(nexts) => nexts,
+        State::Atom(..) | State::Accept => &[],
+    }
+}
+
+/// the state `idx` moves to after consuming a byte, if it consumes at all.
+fn consumed(nfa: &Nfa, idx: usize) -> Option<usize> {
+    match nfa.state(idx) {
+        State::Atom(Atom::Star, _) => Some(idx),
+        State::Atom(_, next) => Some(*next),
+        State::Split(_) | State::Accept => None,
+    }
+}
+
+fn consumes(state: &State, byte: u8) -> bool {
+    match state {
+        State::Atom(atom, _) => *atom == Atom::Star || atom.matches(byte),
+        State::Split(_) | State::Accept => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::Acm;
+
+    /// assert `a` and `b` overlap, and that both grant the witness.
+    fn assert_overlap(a: (&str, &str), b: (&str, &str)) -> (String, String) {
+        let (action, resource) = overlap(a, b).unwrap_or_else(|| panic!("{a:?} {b:?}"));
+        for (pattern_action, pattern_resource) in [a, b] {
+            let mut acm = Acm::new();
+            acm.allow(pattern_action, pattern_resource);
+            assert!(acm.enforce(&action, &resource), "{action} {resource}");
+        }
+        (action, resource)
+    }
+
+    #[test]
+    fn literals_overlap_when_equal() {
+        let witness = assert_overlap(
+            ("action:Get", "resource/path"),
+            ("action:Get", "resource/path"),
+        );
+        assert_eq!(witness, ("action:Get".into(), "resource/path".into()));
+        assert_eq!(
+            overlap(
+                ("action:Get", "resource/path"),
+                ("action:Get", "resource/other")
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn literals_and_globs() {
+        let witness = assert_overlap(
+            ("action:*", "resource/path"),
+            ("action:Delete", "resource/*"),
+        );
+        assert_eq!(witness, ("action:Delete".into(), "resource/path".into()));
+        assert_eq!(
+            overlap(("action:Get*", "resource/path"), ("action:Put", "*/*")),
+            None
+        );
+    }
+
+    #[test]
+    fn globs() {
+        assert_overlap(("action:*", "teams/team-*"), ("action:Get", "teams/*-ops"));
+        assert_overlap(("action:Get", "a*b*c"), ("action:Get", "*c*b*"));
+        assert_overlap(("action:Get", "**"), ("action:Get", "x?z"));
+        assert_overlap(("action:{Get,Put}*", "r"), ("action:*Object", "r"));
+        assert_eq!(
+            overlap(
+                ("action:Get", "teams/team-*"),
+                ("action:Get", "teams/ops-*")
+            ),
+            None
+        );
+        assert_eq!(
+            overlap(("action:Get", "a*"), ("action:Get", "*b?c")),
+            Some(("action:Get".into(), "abac".into()))
+        );
+    }
+
+    #[test]
+    fn alternates_are_searched_without_expanding() {
+        // 2^16 expansions each, which pairwise would be 2^32 searches
+        let a = "{a,b}".repeat(16) + "*";
+        let b = "*".to_string() + &"{b,c}".repeat(16);
+        let (_, resource) = assert_overlap(("action:Get", &a), ("action:Get", &b));
+        assert_eq!(resource, "b".repeat(16));
+    }
+
+    #[test]
+    fn witnesses_are_utf8() {
+        // a class with 'é' matches either of its bytes alone, which no
+        // string of one byte is
+        assert_eq!(glob_witness("[é]", "*", b"/"), None);
+        let witness = glob_witness("[é]?", "*", b"/").unwrap();
+        assert!(SegmentGlob::new("[é]?").unwrap().is_match(&witness));
+        assert_overlap(("action:Get", "[é]*"), ("action:Get", "*\u{e9}"));
+        assert_overlap(("action:Get", "?*"), ("action:Get", "*\u{1F980}"));
+    }
+
+    #[test]
+    fn character_classes() {
+        assert_overlap(("action:Get", "[a-c]*"), ("action:Get", "*[x-z]"));
+        assert_overlap(("action:Get", "[!a-m]*"), ("action:Get", "[k-z]*"));
+        assert_eq!(
+            overlap(("action:Get", "[a-c]*"), ("action:Get", "[!a-c]*")),
+            None
+        );
+        assert_eq!(overlap(("action:Get", "[ab]*"), ("action:Get", "c*")), None);
+    }
+
+    #[test]
+    fn witnesses_split_like_the_first_pattern() {
+        // the segments line up, however they're split
+        let witness = assert_overlap(("a:b", "c/d"), ("a", "*/c/d"));
+        assert_eq!(witness, ("a:b".into(), "c/d".into()));
+        // a `/` would split the resource segment in two
+        assert_eq!(overlap(("a:b", "c*d"), ("a:b", "c/*")), None);
+        assert_eq!(overlap(("action:Get", "*"), ("action:Get", "a*/")), None);
+    }
+
+    #[test]
+    fn differing_lengths_never_overlap() {
+        assert_eq!(overlap(("action:*", "*"), ("action:*", "*/*")), None);
+    }
+}
@@ -0,0 +1,61 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::glob::SegmentGlob;
+use crate::overlap::glob_witness;
+use crate::trie::TrieNode;
+
+/// a glob which matches every segment.
+pub(crate) const ANY_SEGMENT: &str = "*";
+
+/// a single segment of a rule or query.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum Pattern<'p> {
+    Literal(&'p str),
+    Glob(&'p str, &'p SegmentGlob),
+}
+
+impl<'p> Pattern<'p> {
+    pub(crate) fn of(node: &'p TrieNode) -> Self {
+        match node.glob() {
+            Some(glob) => Pattern::Glob(node.raw_pattern(), glob),
+            None => Pattern::Literal(node.raw_pattern()),
+        }
+    }
+
+    pub(crate) fn raw(self) -> &'p str {
+        match self {
+            Pattern::Literal(raw) | Pattern::Glob(raw, _) => raw,
+        }
+    }
+
+    /// whether some segment matches both patterns.
+    pub(crate) fn may_overlap(self, other: Pattern<'_>) -> bool {
+        match (self, other) {
+            (Pattern::Literal(a), Pattern::Literal(b)) => a == b,
+            (Pattern::Glob(_, glob), Pattern::Literal(literal))
+            | (Pattern::Literal(literal), Pattern::Glob(_, glob)) => glob.is_match(literal),
+            (Pattern::Glob(a, _), Pattern::Glob(b, _)) => glob_witness(a, b, &[]).is_some(),
+        }
+    }
+
+    /// whether every segment matching `other` certainly matches this
+    /// pattern too.
+    pub(crate) fn covers(self, other: Pattern<'_>) -> bool {
+        match (self, other) {
+            (Pattern::Literal(a), Pattern::Literal(b)) => a == b,
+            (Pattern::Glob(_, glob), Pattern::Literal(literal)) => glob.is_match(literal),
+            (Pattern::Literal(_), Pattern::Glob(..)) => false,
+            (Pattern::Glob(a, _), Pattern::Glob(b, _)) => a == ANY_SEGMENT || a == b,
+        }
+    }
+}
+
+/// the raw patterns of `patterns`, joined by `separator`.
+pub(crate) fn join(patterns: &[Pattern<'_>], separator: &str) -> String {
+    patterns
+        .iter()
+        .map(|pattern| pattern.raw())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
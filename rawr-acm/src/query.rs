@@ -0,0 +1,332 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::acm::{ACTION_SEPARATOR, RESOURCE_SEPARATOR};
+use crate::glob::{InvalidGlob, SegmentGlob};
+use crate::pattern::{ANY_SEGMENT, Pattern, join};
+use crate::trie::{Trie, TrieNode};
+
+/// how the deny rules affect an allow rule.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Denial {
+    None,
+    Partial,
+    Full,
+}
+
+/// an allow rule which may grant requests within a query, see `Acm::query`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grant {
+    action: String,
+    resource: String,
+    partially_denied: bool,
+}
+
+impl Grant {
+    /// the rule's action pattern.
+    pub fn action(&self) -> &str {
+        &self.action
+    }
+
+    /// the rule's resource pattern.
+    pub fn resource(&self) -> &str {
+        &self.resource
+    }
+
+    /// whether a deny rule may override this rule for some (but not
+    /// necessarily all) of the requests it matches.
+    pub fn is_partially_denied(&self) -> bool {
+        self.partially_denied
+    }
+}
+
+/// the segments of an `Acm::query`, compiled.
+pub(crate) struct Query {
+    // each segment, and its glob if it has one.
+    segments: Vec<(String, Option<SegmentGlob>)>,
+}
+
+impl Query {
+    pub(crate) fn new(action: &str, resource_prefix: &str) -> Result<Self, InvalidGlob> {
+        let resource = resource_prefix
+            .split(RESOURCE_SEPARATOR)
+            .filter(|_| !resource_prefix.is_empty());
+
+        let segments = action
+            .split(ACTION_SEPARATOR)
+            .chain(resource)
+            .map(|segment| {
+                let glob = match segment.contains(ANY_SEGMENT) {
+                    true => {
+                        Some(SegmentGlob::new(segment).ok_or_else(|| InvalidGlob::new(segment))?)
+                    }
+                    false => None,
+                };
+                Ok((segment.into(), glob))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Query { segments })
+    }
+
+    fn pattern(&self, idx: usize) -> Option<Pattern<'_>> {
+        self.segments.get(idx).map(|(raw, glob)| match glob {
+            Some(glob) => Pattern::Glob(raw, glob),
+            None => Pattern::Literal(raw),
+        })
+    }
+
+    pub(crate) fn run(&self, allow: &Trie, deny: &Trie) -> Vec<Grant> {
+        let mut walk = Walk {
+            query: self,
+            deny: deny.root(),
+            rule: Vec::new(),
+            grants: Vec::new(),
+        };
+        walk.node(allow.root());
+        walk.grants
+    }
+}
+
+/// a depth-first walk of the allow trie, pruning rules which can't match the
+/// query.
+struct Walk<'q, 't> {
+    query: &'q Query,
+    deny: &'t TrieNode,
+    // the segments of the rule so far.
+    rule: Vec<Pattern<'t>>,
+    grants: Vec<Grant>,
+}
+
+impl<'t> Walk<'_, 't> {
+    /// push `pattern` onto the rule, if it may overlap the query.
+    fn push(&mut self, pattern: Pattern<'t>) -> bool {
+        let matches = self
+            .query
+            .pattern(self.rule.len())
+            .is_none_or(|query| query.may_overlap(pattern));
+        if matches {
+            self.rule.push(pattern);
+        }
+        matches
+    }
+
+    fn node(&mut self, node: &'t TrieNode) {
+        let depth = self.rule.len();
+        for segment in node.tail() {
+            if !self.push(Pattern::Literal(segment)) {
+                self.rule.truncate(depth);
+                return;
+            }
+        }
+
+        if node.is_terminal() && self.rule.len() >= self.query.segments.len() {
+            for split in node.splits() {
+                self.grant(split.action_len);
+            }
+        }
+
+        for (_, child) in node.literal_children() {
+            self.child(child);
+        }
+        for child in node.glob_children() {
+            self.child(child);
+        }
+        self.rule.truncate(depth);
+    }
+
+    fn child(&mut self, child: &'t TrieNode) {
+        let depth = self.rule.len();
+        if self.push(Pattern::of(child)) {
+            self.node(child);
+        }
+        self.rule.truncate(depth);
+    }
+
+    /// grant the rule so far, which ends at a node whose rule has
+    /// `action_len` action segments.
+    fn grant(&mut self, action_len: usize) {
+        let denial = denial(self.deny, &self.rule);
+        if denial == Denial::Full {
+            return;
+        }
+
+        let (action, resource) = self.rule.split_at(action_len.min(self.rule.len()));
+        self.grants.push(Grant {
+            action: join(action, ACTION_SEPARATOR),
+            resource: join(resource, RESOURCE_SEPARATOR),
+            partially_denied: denial == Denial::Partial,
+        });
+    }
+}
+
+/// how the deny rules beneath `deny` affect `rule`.
+fn denial(deny: &TrieNode, rule: &[Pattern<'_>]) -> Denial {
+    // a deny rule ending here covers `rule` if every one of its segments
+    // covers the corresponding segment of `rule`.
+    let mut stack: Vec<(&TrieNode, usize, bool)> = Vec::from([(deny, 0, true)]);
+    let mut denial = Denial::None;
+
+    while let Some((node, idx, mut covered)) = stack.pop() {
+        let tail = node.tail();
+        let Some(patterns) = rule.get(idx..idx + tail.len()) else {
+            continue;
+        };
+        let mut overlaps = true;
+        for (segment, pattern) in tail.iter().zip(patterns) {
+            let segment = Pattern::Literal(segment);
+            overlaps &= segment.may_overlap(*pattern);
+            covered &= segment.covers(*pattern);
+        }
+        if !overlaps {
+            continue;
+        }
+        let idx = idx + tail.len();
+
+        if idx == rule.len() {
+            if node.is_terminal() {
+                if covered {
+                    return Denial::Full;
+                }
+                denial = Denial::Partial;
+            }
+            continue;
+        }
+
+        let children = node
+            .literal_children()
+            .iter()
+            .map(|(_, child)| child)
+            .chain(node.glob_children());
+        for child in children {
+            let segment = Pattern::of(child);
+            if segment.may_overlap(rule[idx]) {
+                stack.push((child, idx + 1, covered && segment.covers(rule[idx])));
+            }
+        }
+    }
+
+    denial
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use super::*;
+    use crate::Acm;
+
+    fn acm() -> Acm {
+        let mut acm = Acm::new();
+        acm.allow("project:Get", "resource/projects/*");
+        acm.allow("project:*", "resource/projects/*/members");
+        acm.allow("project:Delete", "resource/projects/archived");
+        acm.allow("project:Update", "resource/projects/team-*");
+        acm.allow("billing:Get", "resource/accounts/*");
+        acm.allow("project:Get", "resource/other");
+        acm.deny("project:Get", "resource/projects/secret");
+        acm.deny("project:Delete", "resource/projects/*");
+        acm.deny("project:Update", "resource/projects/*-ops");
+        acm
+    }
+
+    fn summary(grants: &[Grant]) -> Vec<(&str, &str, bool)> {
+        grants
+            .iter()
+            .map(|grant| {
+                (
+                    grant.action(),
+                    grant.resource(),
+                    grant.is_partially_denied(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn query_under_a_resource_prefix() {
+        let grants = acm().query("project:*", "resource/projects/*").unwrap();
+        assert_eq!(
+            summary(&grants),
+            vec![
+                ("project:Get", "resource/projects/*", true),
+                ("project:Update", "resource/projects/team-*", true),
+                ("project:*", "resource/projects/*/members", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_a_single_action() {
+        let grants = acm().query("project:Get", "resource").unwrap();
+        assert_eq!(
+            summary(&grants),
+            vec![
+                ("project:Get", "resource/other", false),
+                ("project:Get", "resource/projects/*", true),
+                ("project:*", "resource/projects/*/members", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn query_omits_fully_denied_rules() {
+        let grants = acm()
+            .query("project:Delete", "resource/projects/archived")
+            .unwrap();
+        assert_eq!(
+            summary(&grants),
+            vec![("project:*", "resource/projects/*/members", false)]
+        );
+    }
+
+    #[test]
+    fn query_everything() {
+        let grants = acm().query("*:*", "").unwrap();
+        assert_eq!(grants.len(), 5);
+        assert!(
+            grants
+                .iter()
+                .any(|grant| grant.action() == "billing:Get" && !grant.is_partially_denied())
+        );
+        assert!(Acm::new().query("*:*", "").unwrap().is_empty());
+    }
+
+    #[test]
+    fn grants_split_like_their_rules() {
+        let mut acm = Acm::new();
+        acm.allow("svc:sub:Get", "x/y");
+        acm.allow("svc:Get", "x/y/z");
+        acm.allow("svc", "sub/Get/x");
+
+        let grants = acm.query("svc:*", "").unwrap();
+        assert_eq!(
+            summary(&grants),
+            vec![
+                ("svc:Get", "x/y/z", false),
+                ("svc", "sub/Get/x", false),
+                ("svc:sub:Get", "x/y", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn grants_keep_every_split_of_a_rule() {
+        let mut acm = Acm::new();
+        acm.allow("a:b", "c/d");
+        acm.allow("a:b:c", "d");
+
+        let grants = acm.query("a:*", "").unwrap();
+        assert_eq!(
+            summary(&grants),
+            vec![("a:b", "c/d", false), ("a:b:c", "d", false)]
+        );
+    }
+
+    #[test]
+    fn invalid_query_globs_are_errors() {
+        assert_eq!(
+            acm().query("project:*", "resource/a[*").unwrap_err(),
+            InvalidGlob::new("a[*")
+        );
+    }
+}
@@ -0,0 +1,284 @@
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use std::sync::{Mutex, MutexGuard};
+
+use arc_swap::ArcSwap;
+
+use crate::acm::{ACTION_SEPARATOR, RESOURCE_SEPARATOR};
+use crate::glob::SegmentGlob;
+use crate::trie::WILDCARD;
+use crate::{Acm, FrozenAcm, InvalidGlob};
+
+/// an `Acm` shared between concurrent readers and writers, created via
+/// `SharedAcm::new`.
+///
+/// readers evaluate an immutable `FrozenAcm` snapshot, published by an
+/// atomic pointer swap, so `SharedAcm::enforce` never waits on a writer (or
+/// another reader). writers batch their changes in a `Transaction`, which
+/// builds and publishes the next snapshot on commit.
+///
+/// every commit freezes the whole rule set, so prefer one transaction per
+/// batch of changes over one per rule.
+#[derive(Debug)]
+pub struct SharedAcm {
+    snapshot: ArcSwap<FrozenAcm>,
+    // every rule committed so far, locked for the lifetime of a transaction
+    // so writers are serialized.
+    rules: Mutex<Rules>,
+}
+
+#[derive(Debug)]
+struct Rules {
+    acm: Acm,
+    // set while a commit applies its rules, so a panic part way through
+    // leaves it set.
+    applying: bool,
+}
+
+impl Default for SharedAcm {
+    fn default() -> Self {
+        Self::new(Acm::new())
+    }
+}
+
+impl SharedAcm {
+    pub fn new(acm: Acm) -> Self {
+        SharedAcm {
+            snapshot: ArcSwap::from_pointee(acm.freeze()),
+            rules: Mutex::new(Rules {
+                acm,
+                applying: false,
+            }),
+        }
+    }
+
+    pub fn enforce(&self, action: &str, resource_path: &str) -> bool {
+        self.snapshot.load().enforce(action, resource_path)
+    }
+
+    /// every request is evaluated against the same snapshot, even if a
+    /// transaction commits part way through.
+    pub fn enforce_batch(&self, requests: &[(&str, &str)]) -> Vec<bool> {
+        self.snapshot.load().enforce_batch(requests)
+    }
+
+    /// the current snapshot, which stays valid (and unchanged) for as long as
+    /// it's held.
+    pub fn snapshot(&self) -> Arc<FrozenAcm> {
+        self.snapshot.load_full()
+    }
+
+    /// start a batch of changes, waiting for any other transaction to finish
+    /// first. nothing is visible to readers until `Transaction::commit`.
+    ///
+    /// # panics
+    ///
+    /// if an earlier commit panicked part way through applying its rules,
+    /// which leaves them half-applied. a panic anywhere else while holding a
+    /// transaction (e.g. while staging) leaves the rules untouched, so isn't
+    /// a problem.
+    pub fn transaction(&self) -> Transaction<'_> {
+        let rules = self.rules.lock().unwrap_or_else(|poisoned| {
+            let rules = poisoned.into_inner();
+            assert!(
+                !rules.applying,
+                "a SharedAcm commit panicked part way through, its rules are half-applied"
+            );
+            self.rules.clear_poison();
+            rules
+        });
+
+        Transaction {
+            rules,
+            snapshot: &self.snapshot,
+            staged: Vec::new(),
+        }
+    }
+}
+
+/// check every glob segment of a rule compiles, where `Acm::allow` and
+/// `Acm::deny` would panic.
+fn validate_rule(action: &str, resource_path: &str) -> Result<(), InvalidGlob> {
+    action
+        .split(ACTION_SEPARATOR)
+        .chain(resource_path.split(RESOURCE_SEPARATOR))
+        .filter(|segment| segment.contains(WILDCARD))
+        .find(|segment| !SegmentGlob::is_valid(segment))
+        .map_or(Ok(()), |segment| Err(InvalidGlob::new(segment)))
+}
+
+#[derive(Debug)]
+enum Rule {
+    Allow(String, String),
+    Deny(String, String),
+}
+
+/// a batch of changes to a `SharedAcm`, created via `SharedAcm::transaction`.
+///
+/// dropping a transaction without committing discards its changes.
+#[derive(Debug)]
+pub struct Transaction<'a> {
+    rules: MutexGuard<'a, Rules>,
+    snapshot: &'a ArcSwap<FrozenAcm>,
+    staged: Vec<Rule>,
+}
+
+impl Transaction<'_> {
+    pub fn allow(&mut self, action: &str, resource_path: &str) {
+        self.staged
+            .push(Rule::Allow(action.to_string(), resource_path.to_string()));
+    }
+
+    pub fn deny(&mut self, action: &str, resource_path: &str) {
+        self.staged
+            .push(Rule::Deny(action.to_string(), resource_path.to_string()));
+    }
+
+    /// apply the staged changes and publish the next snapshot. readers still
+    /// holding the previous snapshot keep using it until they're done.
+    ///
+    /// if any staged rule has an invalid glob, nothing is applied and the
+    /// transaction is discarded.
+    pub fn commit(mut self) -> Result<(), InvalidGlob> {
+        for rule in &self.staged {
+            let (Rule::Allow(action, resource_path) | Rule::Deny(action, resource_path)) = rule;
+            validate_rule(action, resource_path)?;
+        }
+
+        let rules = &mut *self.rules;
+        rules.applying = true;
+        for rule in self.staged.drain(..) {
+            match rule {
+                Rule::Allow(action, resource_path) => rules.acm.allow(&action, &resource_path),
+                Rule::Deny(action, resource_path) => rules.acm.deny(&action, &resource_path),
+            }
+        }
+        self.snapshot.store(Arc::new(rules.acm.freeze()));
+        rules.applying = false;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn commit_publishes_a_snapshot() {
+        let shared = SharedAcm::default();
+        let before = shared.snapshot();
+
+        let mut txn = shared.transaction();
+        txn.allow("action:Get", "resource/*");
+        txn.deny("action:Get", "resource/sensitive");
+        assert!(!shared.enforce("action:Get", "resource/path"));
+        txn.commit().unwrap();
+
+        assert!(shared.enforce("action:Get", "resource/path"));
+        assert!(!shared.enforce("action:Get", "resource/sensitive"));
+        // earlier snapshots are unchanged
+        assert!(!before.enforce("action:Get", "resource/path"));
+    }
+
+    #[test]
+    fn dropped_transactions_are_discarded() {
+        let mut acm = Acm::new();
+        acm.allow("action:Get", "resource/path");
+        let shared = SharedAcm::new(acm);
+
+        let mut txn = shared.transaction();
+        txn.deny("action:Get", "resource/path");
+        drop(txn);
+
+        let mut txn = shared.transaction();
+        txn.allow("action:Get", "other/path");
+        txn.commit().unwrap();
+
+        assert!(shared.enforce("action:Get", "resource/path"));
+        assert!(shared.enforce("action:Get", "other/path"));
+    }
+
+    #[test]
+    fn invalid_globs_are_rejected_whole() {
+        let mut acm = Acm::new();
+        acm.allow("action:Get", "resource/path");
+        let shared = SharedAcm::new(acm);
+        let before = shared.snapshot();
+
+        let mut txn = shared.transaction();
+        txn.allow("action:Get", "other/path");
+        txn.deny("action:Get", "resource/a[*");
+        assert_eq!(txn.commit(), Err(InvalidGlob::new("a[*")));
+
+        // nothing was applied or published
+        assert!(Arc::ptr_eq(&before, &shared.snapshot()));
+        assert!(!shared.enforce("action:Get", "other/path"));
+
+        let mut txn = shared.transaction();
+        txn.allow("action:Get", "third/path");
+        txn.commit().unwrap();
+        assert!(shared.enforce("action:Get", "resource/path"));
+        assert!(!shared.enforce("action:Get", "other/path"));
+        assert!(shared.enforce("action:Get", "third/path"));
+    }
+
+    #[test]
+    fn panics_while_staging_leave_the_rules_usable() {
+        let shared = SharedAcm::default();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let mut txn = shared.transaction();
+            txn.allow("action:Get", "resource/path");
+            panic!("staging failed");
+        }));
+        assert!(result.is_err());
+
+        let mut txn = shared.transaction();
+        txn.allow("action:Get", "other/path");
+        txn.commit().unwrap();
+        assert!(!shared.enforce("action:Get", "resource/path"));
+        assert!(shared.enforce("action:Get", "other/path"));
+    }
+
+    #[test]
+    fn readers_see_whole_transactions() {
+        let shared = SharedAcm::default();
+        let done = AtomicBool::new(false);
+
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    // both rules land in the same transaction, so a reader
+                    // never sees one without the other
+                    let results = shared
+                        .enforce_batch(&[("action:Get", "team-a/x"), ("action:Get", "team-b/x")]);
+                    assert_eq!(results[0], results[1]);
+                    let snapshot = shared.snapshot();
+                    assert_eq!(
+                        snapshot.enforce("action:Get", "team-a/x"),
+                        snapshot.enforce("action:Get", "team-b/x"),
+                    );
+                }
+            });
+
+            for round in 0..100 {
+                let mut txn = shared.transaction();
+                if round % 2 == 0 {
+                    txn.allow("action:Get", "team-a/*");
+                    txn.allow("action:Get", "team-b/*");
+                } else {
+                    txn.deny("action:Get", "team-a/*");
+                    txn.deny("action:Get", "team-b/*");
+                }
+                txn.commit().unwrap();
+            }
+            done.store(true, Ordering::Relaxed);
+        });
+
+        // the last round denied both
+        assert!(!shared.enforce("action:Get", "team-a/x"));
+    }
+}
@@ -1,7 +1,16 @@
-use globset::{Glob, GlobMatcher};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter::Peekable;
+
 use smallvec::SmallVec;
 
-const WILDCARD: &str = "*";
+use crate::glob::SegmentGlob;
+#[cfg(feature = "std")]
+use crate::glob::SegmentGlobSet;
+use crate::pattern::Pattern;
+
+pub(crate) const WILDCARD: &str = "*";
 const ROOT_PATTERN: &str = ".";
 
 // this sets the capacity of the SmallVec-based stack used in
@@ -10,20 +19,45 @@ const ROOT_PATTERN: &str = ".";
 // of the 20% we need to tackle - and when.
 pub(crate) const STACK_CAPACITY: usize = 10;
 
+// nodes with at least this many glob children match them all at once, via a
+// `SegmentGlobSet`. below it, a few `GlobMatcher` calls are cheaper than
+// consulting the set.
+pub(crate) const GLOB_SET_THRESHOLD: usize = 4;
+
 #[derive(Debug)]
 pub(crate) enum NodePattern {
     Root,
     Literal,
-    Glob(GlobMatcher),
+    Glob(SegmentGlob),
+}
+
+/// one way a rule ending at a node was split into action and resource
+/// segments, e.g. `a:b c/d` and `a:b:c d` end at the same node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Split {
+    /// how many of the rule's segments are the action.
+    pub(crate) action_len: usize,
+    /// how many times the rule was inserted with this split.
+    pub(crate) insertions: usize,
 }
 
 #[derive(Debug)]
 pub(crate) struct TrieNode {
     literal_children: Vec<(String, TrieNode)>,
     glob_children: Vec<TrieNode>,
+    // indices into `glob_children`, sorted by `raw_pattern`.
+    glob_index: Vec<usize>,
+    #[cfg(feature = "std")]
+    glob_set: SegmentGlobSet,
     pattern: NodePattern,
     raw_pattern: String,
+    // literal segments which must follow this node's own segment, i.e. a
+    // path-compressed run of single-child literal nodes.
+    tail: Vec<String>,
     terminal: bool,
+    // for terminal nodes, every split the rule was inserted with, sorted by
+    // `action_len`.
+    splits: Vec<Split>,
 }
 
 impl TrieNode {
@@ -31,9 +65,14 @@ impl TrieNode {
         TrieNode {
             literal_children: Vec::new(),
             glob_children: Vec::new(),
+            glob_index: Vec::new(),
+            #[cfg(feature = "std")]
+            glob_set: SegmentGlobSet::default(),
             pattern,
             raw_pattern: raw_pattern.to_string(),
+            tail: Vec::new(),
             terminal: false,
+            splits: Vec::new(),
         }
     }
 
@@ -41,9 +80,14 @@ impl TrieNode {
         TrieNode {
             literal_children: Vec::new(),
             glob_children: Vec::new(),
+            glob_index: Vec::new(),
+            #[cfg(feature = "std")]
+            glob_set: SegmentGlobSet::default(),
             pattern: NodePattern::Root,
             raw_pattern: ROOT_PATTERN.to_string(),
+            tail: Vec::new(),
             terminal: false,
+            splits: Vec::new(),
         }
     }
 
@@ -63,19 +107,194 @@ impl TrieNode {
     }
 
     pub(crate) fn get_or_insert_glob(&mut self, segment_pattern: &str) -> &mut TrieNode {
-        if let Some(idx) = self
-            .glob_children
+        let glob_children = &self.glob_children;
+        match self.glob_index.binary_search_by(|idx| {
+            glob_children[*idx]
+                .raw_pattern
+                .as_str()
+                .cmp(segment_pattern)
+        }) {
+            Ok(pos) => &mut self.glob_children[self.glob_index[pos]],
+            Err(pos) => {
+                let pattern = SegmentGlob::new(segment_pattern).expect("invalid glob pattern");
+                let new_node = TrieNode::new(NodePattern::Glob(pattern), segment_pattern);
+                self.glob_children.push(new_node);
+                let last_idx = self.glob_children.len() - 1;
+                self.glob_index.insert(pos, last_idx);
+                #[cfg(feature = "std")]
+                self.glob_set.invalidate();
+                &mut self.glob_children[last_idx]
+            }
+        }
+    }
+
+    /// call `f` with every glob child matching `segment`, in insertion order.
+    fn for_each_glob_match<'s>(&'s self, segment: &str, mut f: impl FnMut(&'s TrieNode)) {
+        #[cfg(feature = "std")]
+        if self.glob_children.len() >= GLOB_SET_THRESHOLD {
+            self.glob_set
+                .compiled(self.glob_children.iter().map(|c| c.raw_pattern.as_str()))
+                .for_each_match(segment, |idx| f(&self.glob_children[idx]));
+            return;
+        }
+
+        for glob_child in &self.glob_children {
+            if let NodePattern::Glob(matcher) = &glob_child.pattern
+                && matcher.is_match(segment)
+            {
+                f(glob_child);
+            }
+        }
+    }
+
+    /// the literal children, sorted by segment.
+    pub(crate) fn literal_children(&self) -> &[(String, TrieNode)] {
+        &self.literal_children
+    }
+
+    /// the glob children, in insertion order.
+    pub(crate) fn glob_children(&self) -> &[TrieNode] {
+        &self.glob_children
+    }
+
+    /// the segment pattern this node was inserted with.
+    pub(crate) fn raw_pattern(&self) -> &str {
+        &self.raw_pattern
+    }
+
+    /// the compiled glob, for glob nodes.
+    pub(crate) fn glob(&self) -> Option<&SegmentGlob> {
+        match &self.pattern {
+            NodePattern::Glob(glob) => Some(glob),
+            _ => None,
+        }
+    }
+
+    /// the literal segments compressed into this node, after its own.
+    pub(crate) fn tail(&self) -> &[String] {
+        &self.tail
+    }
+
+    /// whether a rule ends at this node.
+    pub(crate) fn is_terminal(&self) -> bool {
+        self.terminal
+    }
+
+    /// call `f` with the segment patterns of every rule ending at or beneath
+    /// this node, each prefixed by `rule`, and the node it ends at.
+    fn for_each_rule<'t>(
+        &'t self,
+        rule: &mut Vec<Pattern<'t>>,
+        f: &mut impl FnMut(&[Pattern<'t>], &'t TrieNode),
+    ) {
+        let depth = rule.len();
+        rule.extend(self.tail.iter().map(|segment| Pattern::Literal(segment)));
+        if self.terminal {
+            f(rule, self);
+        }
+
+        let children = self
+            .literal_children
             .iter()
-            .position(|c| c.raw_pattern == segment_pattern)
+            .map(|(_, child)| child)
+            .chain(&self.glob_children);
+        for child in children {
+            rule.push(Pattern::of(child));
+            child.for_each_rule(rule, f);
+            rule.pop();
+        }
+        rule.truncate(depth);
+    }
+
+    /// every split the rule ending here was inserted with, fewest action
+    /// segments first.
+    pub(crate) fn splits(&self) -> &[Split] {
+        &self.splits
+    }
+
+    /// record an insertion of the rule ending here, with `action_len` of its
+    /// segments being the action.
+    pub(crate) fn record_split(&mut self, action_len: usize) {
+        self.add_split(Split {
+            action_len,
+            insertions: 1,
+        });
+    }
+
+    fn add_split(&mut self, split: Split) {
+        match self
+            .splits
+            .binary_search_by_key(&split.action_len, |s| s.action_len)
         {
-            &mut self.glob_children[idx]
-        } else {
-            let pattern = Glob::new(segment_pattern).unwrap().compile_matcher();
-            let new_node = TrieNode::new(NodePattern::Glob(pattern), segment_pattern);
-            self.glob_children.push(new_node);
-            let last_idx = self.glob_children.len() - 1;
-            &mut self.glob_children[last_idx]
+            Ok(idx) => self.splits[idx].insertions += split.insertions,
+            Err(idx) => self.splits.insert(idx, split),
+        }
+    }
+
+    /// whether this node was only just created, i.e. nothing ends at or
+    /// beneath it yet.
+    fn is_fresh(&self) -> bool {
+        !self.terminal && self.literal_children.is_empty() && self.glob_children.is_empty()
+    }
+
+    /// consume as much of this node's tail from `segments` as they match,
+    /// returning the node the rest of the rule should be inserted beneath.
+    ///
+    /// a fresh node absorbs any literal segments which follow it into its
+    /// tail. otherwise, if the rule diverges from (or ends within) the tail,
+    /// the tail is split where they part ways.
+    fn follow_tail<I, T>(&mut self, segments: &mut Peekable<I>) -> &mut TrieNode
+    where
+        I: Iterator<Item = T>,
+        T: AsRef<str>,
+    {
+        if self.is_fresh() {
+            while let Some(segment) = segments.next_if(|s| !s.as_ref().contains(WILDCARD)) {
+                self.tail.push(segment.as_ref().to_string());
+            }
+            return self;
+        }
+
+        for idx in 0..self.tail.len() {
+            if segments.next_if(|s| s.as_ref() == self.tail[idx]).is_none() {
+                self.split_tail(idx);
+                return self;
+            }
+        }
+        self
+    }
+
+    /// split the tail before `idx`, moving `tail[idx]` and everything beneath
+    /// it into a new literal child.
+    fn split_tail(&mut self, idx: usize) {
+        let mut rest_tail = self.tail.split_off(idx);
+        let segment = rest_tail.remove(0);
+
+        let mut rest = TrieNode::new(NodePattern::Literal, &segment);
+        rest.tail = rest_tail;
+        rest.literal_children = core::mem::take(&mut self.literal_children);
+        rest.glob_children = core::mem::take(&mut self.glob_children);
+        rest.glob_index = core::mem::take(&mut self.glob_index);
+        #[cfg(feature = "std")]
+        {
+            rest.glob_set = core::mem::take(&mut self.glob_set);
         }
+        rest.terminal = core::mem::take(&mut self.terminal);
+        rest.splits = core::mem::take(&mut self.splits);
+
+        self.literal_children = vec![(segment, rest)];
+    }
+
+    /// the index of the segment after this node's tail, if `segments` match
+    /// the tail from `idx` onwards.
+    fn match_tail(&self, segments: &[&str], idx: usize) -> Option<usize> {
+        let end = idx + self.tail.len();
+        let candidates = segments.get(idx..end)?;
+        self.tail
+            .iter()
+            .zip(candidates)
+            .all(|(expected, candidate)| expected == candidate)
+            .then_some(end)
     }
 
     pub(crate) fn insert_segment(&mut self, segment_pattern: &str) -> &mut TrieNode {
@@ -101,9 +320,13 @@ impl TrieNode {
 
         // shift(@stack) - *sigh*, dawg i miss Perl
         while let Some((node, idx)) = stack.pop() {
-            // end of the road, bucko
+            // end of the road, bucko. a rule must end here too, otherwise
+            // there may be another route.
             if idx == segments.len() {
-                return node.terminal;
+                if node.terminal {
+                    return true;
+                }
+                continue;
             }
 
             // uhm, like - do you even work here?
@@ -112,17 +335,18 @@ impl TrieNode {
                 .literal_children
                 .binary_search_by(|(k, _)| k.as_str().cmp(current))
             {
-                stack.push((&node.literal_children[pos].1, idx + 1));
+                let child = &node.literal_children[pos].1;
+                if let Some(next) = child.match_tail(segments, idx + 1) {
+                    stack.push((child, next));
+                }
             }
 
             // you can't triple stamp a double stamp!
-            for glob_child in &node.glob_children {
-                if let NodePattern::Glob(matcher) = &glob_child.pattern
-                    && matcher.is_match(current)
-                {
-                    stack.push((glob_child, idx + 1));
+            node.for_each_glob_match(current, |glob_child| {
+                if let Some(next) = glob_child.match_tail(segments, idx + 1) {
+                    stack.push((glob_child, next));
                 }
-            }
+            });
         }
 
         // super sus dude, no za for you
@@ -142,21 +366,182 @@ impl Trie {
         }
     }
 
-    pub(crate) fn insert<I>(&mut self, segments: I)
+    /// insert a rule, returning the node it ends at. the caller records how
+    /// it's split, see `TrieNode::record_split`.
+    pub(crate) fn insert<I>(&mut self, segments: I) -> &mut TrieNode
     where
         I: IntoIterator,
         I::Item: AsRef<str>,
     {
+        let mut segments = segments.into_iter().peekable();
         let mut node = &mut self.root;
 
-        for segment in segments {
-            node = node.insert_segment(segment.as_ref());
+        while let Some(segment) = segments.next() {
+            node = node
+                .insert_segment(segment.as_ref())
+                .follow_tail(&mut segments);
         }
 
-        node.terminal = true
+        node.terminal = true;
+        node
     }
 
     pub(crate) fn contains(&self, segments: &SmallVec<[&str; STACK_CAPACITY]>) -> bool {
         self.root.contains(segments)
     }
+
+    pub(crate) fn root(&self) -> &TrieNode {
+        &self.root
+    }
+
+    /// insert every rule of `other`.
+    pub(crate) fn extend(&mut self, other: &Trie) {
+        other.for_each_rule(|rule, end| {
+            let node = self.insert(rule.iter().map(|pattern| pattern.raw()));
+            for split in end.splits() {
+                node.add_split(*split);
+            }
+        });
+    }
+
+    /// call `f` with the segment patterns of every rule, and the node it ends
+    /// at, depth-first with literals before globs.
+    pub(crate) fn for_each_rule<'t>(&'t self, mut f: impl FnMut(&[Pattern<'t>], &'t TrieNode)) {
+        self.root.for_each_rule(&mut Vec::new(), &mut f);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    const PATTERNS: &[&str] = &[
+        "team-a-*",
+        "team-b-*",
+        "team-*-ops",
+        "*-ops",
+        "team-?-*",
+        "*",
+        "team-c*",
+    ];
+
+    /// the number of nodes beneath (and including) `node`.
+    fn node_count(node: &TrieNode) -> usize {
+        1 + node
+            .literal_children()
+            .iter()
+            .map(|(_, child)| node_count(child))
+            .chain(node.glob_children().iter().map(node_count))
+            .sum::<usize>()
+    }
+
+    fn contains(trie: &Trie, rule: &str) -> bool {
+        let segments: SmallVec<[&str; STACK_CAPACITY]> = rule.split('/').collect();
+        trie.contains(&segments)
+    }
+
+    #[test]
+    fn literal_runs_are_compressed() {
+        let mut trie = Trie::new();
+        trie.insert("iam:GetUser/accounts/36UeVtK7/users/naomi".split('/'));
+        assert_eq!(node_count(trie.root()), 2);
+        assert_eq!(
+            trie.root().literal_children()[0].1.tail(),
+            ["accounts", "36UeVtK7", "users", "naomi"]
+        );
+
+        // globs end a run, and start a new one
+        trie.insert("iam:GetUser/accounts/*/users/amos".split('/'));
+        assert_eq!(node_count(trie.root()), 4);
+
+        assert!(contains(&trie, "iam:GetUser/accounts/36UeVtK7/users/naomi"));
+        assert!(contains(&trie, "iam:GetUser/accounts/36UeVtK7/users/amos"));
+        assert!(!contains(&trie, "iam:GetUser/accounts/36UeVtK7/users"));
+        assert!(!contains(
+            &trie,
+            "iam:GetUser/accounts/36UeVtK7/users/naomi/x"
+        ));
+    }
+
+    #[test]
+    fn compressed_runs_split_where_rules_diverge() {
+        let mut trie = Trie::new();
+        trie.insert("a/b/c/d".split('/'));
+        trie.insert("a/b/x".split('/'));
+        trie.insert("a/b".split('/'));
+        trie.insert("a/b/c/d".split('/'));
+
+        // a(b) -> {c(d), x}
+        assert_eq!(node_count(trie.root()), 4);
+        for rule in ["a/b/c/d", "a/b/x", "a/b"] {
+            assert!(contains(&trie, rule), "{rule}");
+        }
+        for rule in ["a", "a/b/c", "a/b/d", "a/x"] {
+            assert!(!contains(&trie, rule), "{rule}");
+        }
+    }
+
+    #[test]
+    fn compressed_runs_split_beneath_globs() {
+        let mut trie = Trie::new();
+        trie.insert("a/b/c".split('/'));
+        trie.insert("a/b/team-*/x".split('/'));
+        trie.insert("a/b/team-*/y".split('/'));
+
+        assert!(contains(&trie, "a/b/c"));
+        assert!(contains(&trie, "a/b/team-a/x"));
+        assert!(contains(&trie, "a/b/team-a/y"));
+        assert!(!contains(&trie, "a/b/team-a"));
+    }
+
+    fn glob_matches<'s>(node: &'s TrieNode, segment: &str) -> Vec<&'s str> {
+        let mut matches = Vec::new();
+        node.for_each_glob_match(segment, |child| matches.push(child.raw_pattern()));
+        matches
+    }
+
+    #[test]
+    fn glob_siblings_are_deduplicated() {
+        let mut node = TrieNode::default();
+        for pattern in PATTERNS.iter().chain(PATTERNS) {
+            node.insert_segment(pattern);
+        }
+        assert_eq!(node.glob_children().len(), PATTERNS.len());
+    }
+
+    #[test]
+    fn glob_matches_are_in_insertion_order() {
+        let mut node = TrieNode::default();
+        for pattern in PATTERNS {
+            node.insert_segment(pattern);
+        }
+
+        for segment in ["team-a-ops", "team-b-dev", "team-c", "billing-ops", "x"] {
+            let expected: Vec<&str> = node
+                .glob_children()
+                .iter()
+                .filter(|child| match &child.pattern {
+                    NodePattern::Glob(matcher) => matcher.is_match(segment),
+                    _ => false,
+                })
+                .map(TrieNode::raw_pattern)
+                .collect();
+            assert_eq!(glob_matches(&node, segment), expected, "{segment}");
+        }
+    }
+
+    #[test]
+    fn glob_siblings_inserted_after_matching_are_matched() {
+        let mut node = TrieNode::default();
+        for team in 0..8 {
+            node.insert_segment(&format!("team-{team}-*"));
+        }
+        assert_eq!(glob_matches(&node, "team-8-ops"), Vec::<&str>::new());
+
+        node.insert_segment("team-8-*");
+        assert_eq!(glob_matches(&node, "team-8-ops"), ["team-8-*"]);
+    }
 }
@@ -0,0 +1,142 @@
+//! allocation-counting tests for the zero-allocation APIs, see
+//! `docs/quality.md`.
+
+// `#[global_allocator]` expands into a 4-argument `realloc` shim.
+#![allow(clippy::too_many_arguments)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+#[cfg(feature = "std")]
+use rawr_acm::SharedAcm;
+use rawr_acm::{Acm, AcmSnapshot};
+
+/// counts allocations made by the current thread, so tests running in
+/// parallel don't see each other's allocations.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// the number of allocations made by `f`.
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    let after = ALLOCATIONS.with(Cell::get);
+    drop(result);
+    after - before
+}
+
+/// requests within `acm-001`, i.e. no more than 10 segments.
+const REQUESTS: &[(&str, &str)] = &[
+    ("action:Get", "resource/path"),
+    ("action:Delete", "resource/sensitive"),
+    ("action:Get", "other/path"),
+    ("identity:CreateUser", "users/team-a/amos"),
+    ("action:Get", "a/b/c/d/e/f/g/h"),
+    ("action:Get", "teams/team-c-ops"),
+];
+
+/// evaluate every request once: `globset` lazily builds a per-thread regex
+/// cache on first use, which is initialization rather than steady state.
+fn warm_up(enforce: impl Fn(&str, &str) -> bool) {
+    for (action, resource) in REQUESTS {
+        enforce(action, resource);
+    }
+}
+
+fn acm() -> Acm {
+    let mut acm = Acm::new();
+    acm.allow("action:*", "resource/*");
+    acm.allow("identity:*User", "users/team-*/*");
+    acm.allow("action:Get", "a/b/c/d/e/f/g/h");
+    acm.deny("action:Delete", "resource/sensitive");
+    for team in ["a", "b", "c", "d", "e"] {
+        acm.allow("action:Get", &format!("teams/team-{team}-*"));
+    }
+    acm
+}
+
+#[test]
+fn enforce_does_not_allocate() {
+    let acm = acm();
+    warm_up(|action, resource| acm.enforce(action, resource));
+    for (action, resource) in REQUESTS {
+        assert_eq!(
+            allocations(|| acm.enforce(action, resource)),
+            0,
+            "{action} {resource}"
+        );
+    }
+}
+
+#[test]
+fn frozen_enforce_does_not_allocate() {
+    let acm = acm().freeze();
+    warm_up(|action, resource| acm.enforce(action, resource));
+    for (action, resource) in REQUESTS {
+        assert_eq!(
+            allocations(|| acm.enforce(action, resource)),
+            0,
+            "{action} {resource}"
+        );
+    }
+}
+
+#[test]
+fn compiled_enforce_does_not_allocate() {
+    let acm = acm().compile();
+    assert!(acm.is_deterministic());
+    warm_up(|action, resource| acm.enforce(action, resource));
+    for (action, resource) in REQUESTS {
+        assert_eq!(
+            allocations(|| acm.enforce(action, resource)),
+            0,
+            "{action} {resource}"
+        );
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn shared_enforce_does_not_allocate() {
+    let acm = SharedAcm::new(acm());
+    warm_up(|action, resource| acm.enforce(action, resource));
+    for (action, resource) in REQUESTS {
+        assert_eq!(
+            allocations(|| acm.enforce(action, resource)),
+            0,
+            "{action} {resource}"
+        );
+    }
+}
+
+#[test]
+fn snapshot_enforce_does_not_allocate() {
+    let bytes = acm().freeze().to_snapshot();
+    let acm = AcmSnapshot::load(&bytes).unwrap();
+    // compiles every glob the requests need
+    warm_up(|action, resource| acm.enforce(action, resource));
+    for (action, resource) in REQUESTS {
+        assert_eq!(
+            allocations(|| acm.enforce(action, resource)),
+            0,
+            "{action} {resource}"
+        );
+    }
+}
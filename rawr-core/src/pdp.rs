@@ -1,7 +1,21 @@
+use std::collections::HashMap;
+
 use async_trait::async_trait;
 
 use crate::{PrincipalId, TenantId};
-use rawr_resource_name::ResourceName;
+use rawr_resource_name::{ResourceName, TemplateVariables};
+
+/// the name under which a request's principal is exposed to resource name
+/// templates, i.e. `${principal}`.
+pub const PRINCIPAL_VARIABLE: &str = "principal";
+
+/// the name under which a request's tenant is exposed to resource name
+/// templates, i.e. `${tenant}`.
+pub const TENANT_VARIABLE: &str = "tenant";
+
+/// additional, caller supplied values describing an authorization request
+/// (e.g. the account the principal belongs to).
+pub type RequestContext = HashMap<String, String>;
 
 /// I know why you're here, Neo. I know what you've been doing... why you
 /// hardly sleep, why you live alone, and why night after night, you sit by
@@ -15,6 +29,19 @@ pub struct AuthorizationRequest<'a> {
     pub principal_id: PrincipalId,
     pub action: &'a str,
     pub resource: ResourceName<'a>,
+    pub context: RequestContext,
+}
+
+/// a request resolves resource name templates: `${principal}` and `${tenant}`
+/// come from the request itself, every other variable from its context.
+impl TemplateVariables for AuthorizationRequest<'_> {
+    fn get(&self, name: &str) -> Option<&str> {
+        match name {
+            PRINCIPAL_VARIABLE => Some(&self.principal_id),
+            TENANT_VARIABLE => self.tenant_id.as_deref(),
+            _ => self.context.get(name).map(String::as_str),
+        }
+    }
 }
 
 // What is the Matrix?
@@ -35,3 +62,41 @@ pub trait PolicyDecisionPoint: Send + Sync {
         request: AuthorizationRequest<'_>,
     ) -> Result<AuthorizationResponse, Self::Error>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rawr_resource_name::{ResourceNameError, ResourceNameTemplate, Span};
+
+    fn request(tenant_id: TenantId) -> AuthorizationRequest<'static> {
+        AuthorizationRequest {
+            tenant_id,
+            principal_id: "anderson-dawes".to_string(),
+            action: "opa:member:read",
+            resource: ResourceName::parse("mrn:tycho:opa:::member/anderson-dawes").unwrap(),
+            context: RequestContext::from([("account".to_string(), "36UeVtK7".to_string())]),
+        }
+    }
+
+    #[test]
+    fn templates_resolve_from_requests() {
+        let template =
+            ResourceNameTemplate::parse("mrn:${tenant}:opa:*:${account}:member/${principal}")
+                .unwrap();
+        assert_eq!(
+            template
+                .resolve(&request(Some("tycho".to_string())))
+                .unwrap()
+                .as_str(),
+            "mrn:tycho:opa:*:36UeVtK7:member/anderson-dawes"
+        );
+
+        // single-tenant requests have no ${tenant} to offer
+        assert_eq!(
+            template.resolve(&request(None)).unwrap_err(),
+            ResourceNameError::UnresolvedVariable {
+                span: Span::new(4, 13)
+            }
+        );
+    }
+}
@@ -21,7 +21,7 @@ pub enum RawrError {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rawr_resource_name::ResourceNameError;
+    use rawr_resource_name::{ResourceNameError, Span};
 
     #[test]
     fn error_wrapping() {
@@ -29,6 +29,7 @@ mod tests {
         let underlying_error = ResourceNameError::InvalidSegmentCount {
             expected: 4,
             found: 20,
+            span: Span::new(12, 42),
         };
 
         // coerce our underlying error into the top-level rawr error
@@ -42,11 +43,15 @@ mod tests {
 
         // verify the internals can be extracted for more specific error
         // handling, (e.g. if we want to handle underlying errors differently).
-        if let RawrError::ResourceName(ResourceNameError::InvalidSegmentCount { expected, found }) =
-            user_facing_error
+        if let RawrError::ResourceName(ResourceNameError::InvalidSegmentCount {
+            expected,
+            found,
+            span,
+        }) = user_facing_error
         {
             assert_eq!(expected, 4);
             assert_eq!(found, 20);
+            assert_eq!(span, Span::new(12, 42));
         }
     }
 }
@@ -12,6 +12,8 @@ const VALID_RESOURCE_NAME_EMPTY_REGION_AND_ACCOUNT_ID: &str =
 const VALID_RESOURCE_NAME_WITH_MULTI_SEGMENT_RESOURCE_PATH: &str =
     "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:station/ceres/bay-12";
 
+const VALID_RESOURCE_NAME_WITH_LONG_RESOURCE_PATH: &str = "krn:konnect:mesh:eu:df40c456-7dbb-4fbf-8b2c-a1c89997b7c4:control-plane/157807aa-3a85-4504-8340-ad9c0baae569/zone/mhq-eu-primary";
+
 fn bench(c: &mut Criterion) {
     // TODO(nick): make this a map so we can have human friendly names...
     let test_cases: HashMap<&str, &str> = HashMap::from([
@@ -24,14 +26,28 @@ fn bench(c: &mut Criterion) {
             "valid_resource_name_with_multi_segment_resource_path",
             VALID_RESOURCE_NAME_WITH_MULTI_SEGMENT_RESOURCE_PATH,
         ),
+        (
+            "valid_resource_name_with_long_resource_path",
+            VALID_RESOURCE_NAME_WITH_LONG_RESOURCE_PATH,
+        ),
     ]);
 
+    let inventory: Vec<&str> = test_cases.values().copied().cycle().take(1024).collect();
+    let inventory = inventory.join("\n");
+
     let mut group = c.benchmark_group("ResourceName::parse");
     for (name, case) in test_cases {
         group.throughput(Throughput::Elements(1));
         group.bench_function(name, |b| b.iter(|| ResourceName::parse(black_box(case))));
     }
     group.finish();
+
+    let mut group = c.benchmark_group("ResourceName::parse_lines");
+    group.throughput(Throughput::Elements(1024));
+    group.bench_function("inventory", |b| {
+        b.iter(|| ResourceName::parse_lines(black_box(inventory.as_bytes())).count())
+    });
+    group.finish();
 }
 
 criterion_group!(benches, bench,);
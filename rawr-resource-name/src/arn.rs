@@ -0,0 +1,308 @@
+use crate::resource_name::{RESOURCE_PATH_SEGMENT_SEPARATOR, SEGMENT_COUNT, SEGMENT_SEPARATOR};
+use crate::scan::scan;
+use crate::{ResourceName, ResourceNameError, Span};
+
+/// the prefix every ARN starts with.
+const ARN_PREFIX: &str = "arn";
+
+/// the AWS partition family, e.g. 'aws', 'aws-cn', 'aws-us-gov'.
+const ARN_PARTITION: &str = "aws";
+
+/// separates the partition family from its variant, e.g. 'aws-cn'.
+const ARN_PARTITION_SEPARATOR: char = '-';
+
+/// AWS ARN compatibility mode.
+///
+/// format:
+/// `arn:{partition}:{service}:{region}:{account_id}:{resource}`
+///
+/// where {resource} is one of:
+/// - `{resource_type}/{resource_path}` (e.g. `role/path/name`)
+/// - `{resource_type}:{resource_path}` (e.g. `function:my-function:alias`)
+/// - `{resource_path}` (e.g. `my-queue`), which has no resource type
+///
+/// whichever separator comes first decides the variant, the remainder may
+/// contain either separator. unlike `ResourceName::parse`, ARNs are allowed to
+/// omit the resource type.
+impl<'a> ResourceName<'a> {
+    /// parse a `ResourceName` from an AWS ARN.
+    ///
+    /// ascii control characters are rejected as `InvalidCharacter`, as they
+    /// are by `ResourceName::parse`.
+    pub fn parse_arn(input: &'a str) -> Result<Self, ResourceNameError> {
+        // did you even bother?
+        if input.is_empty() {
+            return Err(ResourceNameError::Empty);
+        }
+        scan(input.as_bytes()).map_err(|offset| ResourceNameError::InvalidCharacter {
+            span: Span::new(offset, offset + 1),
+        })?;
+
+        // the resource is the only segment which may contain colons, so we
+        // stop splitting once we get to it.
+        let mut segments = input.splitn(SEGMENT_COUNT, SEGMENT_SEPARATOR);
+        let mut next = |found: usize| {
+            segments
+                .next()
+                .ok_or(ResourceNameError::InvalidSegmentCount {
+                    expected: SEGMENT_COUNT,
+                    found,
+                    span: Span::at(input.len()),
+                })
+        };
+
+        let prefix = next(0)?;
+        let partition = next(1)?;
+        let service = next(2)?;
+        let region = next(3)?;
+        let account_id = next(4)?;
+        let resource = next(5)?;
+
+        // verify we have our required segments, region and account_id are
+        // optional (e.g. s3 buckets have neither).
+        if prefix != ARN_PREFIX {
+            return Err(ResourceNameError::InvalidArnPrefix {
+                span: Span::of(input, prefix),
+            });
+        }
+        if partition.is_empty() {
+            return Err(ResourceNameError::EmptyPartition {
+                span: Span::of(input, partition),
+            });
+        }
+        let is_aws_partition = partition
+            .strip_prefix(ARN_PARTITION)
+            .is_some_and(|variant| {
+                variant.is_empty()
+                    || variant
+                        .strip_prefix(ARN_PARTITION_SEPARATOR)
+                        .is_some_and(|v| !v.is_empty())
+            });
+        if !is_aws_partition {
+            return Err(ResourceNameError::InvalidArnPartition {
+                span: Span::of(input, partition),
+            });
+        }
+        if service.is_empty() {
+            return Err(ResourceNameError::EmptyService {
+                span: Span::of(input, service),
+            });
+        }
+        if resource.is_empty() {
+            return Err(ResourceNameError::EmptyQualifiedResourcePath {
+                span: Span::of(input, resource),
+            });
+        }
+
+        // figure out which resource variant we're looking at
+        let (resource_type, resource_separator, resource_path) = match resource.find([':', '/']) {
+            Some(idx) => {
+                let (resource_type, rest) = resource.split_at(idx);
+                let (separator, resource_path) = rest.split_at(1);
+                let resource_separator = if separator == SEGMENT_SEPARATOR {
+                    SEGMENT_SEPARATOR
+                } else {
+                    RESOURCE_PATH_SEGMENT_SEPARATOR
+                };
+                (resource_type, resource_separator, resource_path)
+            }
+            None => ("", "", resource),
+        };
+
+        // verify we have a valid resource type and resource path
+        if !resource_separator.is_empty() && resource_type.is_empty() {
+            return Err(ResourceNameError::EmptyResourceType {
+                span: Span::of(input, resource_type),
+            });
+        }
+        if resource_path.is_empty() {
+            return Err(ResourceNameError::EmptyResourcePath {
+                span: Span::of(input, resource_path),
+            });
+        }
+
+        Ok(ResourceName {
+            prefix,
+            partition,
+            service,
+            region,
+            account_id,
+            resource_type,
+            resource_separator,
+            resource_path,
+        })
+    }
+
+    /// parse with `ResourceName::parse`, falling back to
+    /// `ResourceName::parse_arn`, so a name from either parser survives a
+    /// round trip through its string form. errors are always those of
+    /// `ResourceName::parse`.
+    pub(crate) fn parse_any(input: &'a str) -> Result<Self, ResourceNameError> {
+        ResourceName::parse(input).or_else(|err| ResourceName::parse_arn(input).map_err(|_| err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::ResourceNameBuf;
+
+    #[test]
+    fn parse_arn_type_slash_id() {
+        let s = "arn:aws:iam::123456789012:role/path/name";
+        let rn = ResourceName::parse_arn(s).unwrap();
+        assert_eq!(rn.partition(), "aws");
+        assert_eq!(rn.service(), "iam");
+        assert_eq!(rn.region(), "");
+        assert_eq!(rn.account_id(), "123456789012");
+        assert_eq!(rn.resource_type(), "role");
+        assert_eq!(rn.resource_path(), "path/name");
+        assert_eq!(rn.to_string(), s);
+    }
+
+    #[test]
+    fn parse_arn_type_colon_id() {
+        let s = "arn:aws:lambda:us-east-1:123456789012:function:my-function:live";
+        let rn = ResourceName::parse_arn(s).unwrap();
+        assert_eq!(rn.resource_type(), "function");
+        assert_eq!(rn.resource_path(), "my-function:live");
+        assert_eq!(rn.to_string(), s);
+    }
+
+    #[test]
+    fn parse_arn_bare_id() {
+        let s = "arn:aws:sqs:us-east-1:123456789012:queue-name";
+        let rn = ResourceName::parse_arn(s).unwrap();
+        assert_eq!(rn.resource_type(), "");
+        assert_eq!(rn.resource_path(), "queue-name");
+        assert_eq!(rn.to_string(), s);
+        assert_eq!(rn.to_buf().as_resource_name().resource_path(), "queue-name");
+    }
+
+    #[test]
+    fn parse_arn_s3_object() {
+        // s3 objects have no resource type, so the bucket name stands in for
+        // it and the object key is the resource path.
+        let s = "arn:aws:s3:::rocinante-logs/key/with/slashes";
+        let rn = ResourceName::parse_arn(s).unwrap();
+        assert_eq!(rn.region(), "");
+        assert_eq!(rn.account_id(), "");
+        assert_eq!(rn.resource_type(), "rocinante-logs");
+        assert_eq!(rn.resource_path(), "key/with/slashes");
+        assert_eq!(rn.to_string(), s);
+    }
+
+    #[test]
+    fn arns_round_trip_through_owned_forms() {
+        for s in [
+            "arn:aws:sqs:us-east-1:123456789012:queue-name",
+            "arn:aws:lambda:us-east-1:123456789012:function:my-function:live",
+            "arn:aws:iam::123456789012:role/path/name",
+            "arn:aws:s3:::rocinante-logs/key/with/slashes",
+        ] {
+            let rn = ResourceName::parse_arn(s).unwrap();
+            let buf = rn.to_buf();
+            assert_eq!(buf.as_resource_name(), rn);
+            assert_eq!(ResourceNameBuf::parse(s).unwrap(), rn);
+            assert_eq!(s.parse::<ResourceNameBuf>().unwrap(), rn);
+            assert_eq!(ResourceNameBuf::try_from(s.to_string()).unwrap(), rn);
+            assert_eq!(
+                ResourceName::from_uri_component(&rn.to_uri_component()).unwrap(),
+                rn
+            );
+            assert_eq!(ResourceName::from_urn(&rn.to_urn()).unwrap(), rn);
+        }
+    }
+
+    #[test]
+    fn invalid_arns_report_resource_name_errors() {
+        // neither parser accepts it, so the error is `ResourceName::parse`'s
+        assert_eq!(
+            ResourceNameBuf::parse("arn:aws:sqs:us-east-1:123456789012:").unwrap_err(),
+            ResourceName::parse("arn:aws:sqs:us-east-1:123456789012:").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn parse_arn_rejects_control_characters() {
+        for (s, offset) in [
+            ("arn:aws:s3:::bucket/k\ney", 21),
+            ("arn:aws:iam::123456789012:user/a\0b", 32),
+            ("arn:aws:sqs:us-east-1\u{7f}:123456789012:queue", 21),
+        ] {
+            let err = ResourceNameError::InvalidCharacter {
+                span: Span::new(offset, offset + 1),
+            };
+            assert_eq!(ResourceName::parse_arn(s).unwrap_err(), err, "{s:?}");
+            assert_eq!(ResourceNameBuf::parse(s).unwrap_err(), err, "{s:?}");
+            assert_eq!(
+                ResourceNameBuf::try_from(s.to_string()).unwrap_err(),
+                err,
+                "{s:?}"
+            );
+        }
+        assert!(ResourceName::from_uri_component("arn%3Aaws%3As3%3A%3A%3Ab%2Fk%0A").is_err());
+    }
+
+    #[test]
+    fn parse_arn_partitions() {
+        for partition in ["aws", "aws-cn", "aws-us-gov"] {
+            let s = format!("arn:{partition}:ec2:us-east-1:123456789012:instance/i-0123");
+            let rn = ResourceName::parse_arn(&s).unwrap();
+            assert_eq!(rn.partition(), partition);
+        }
+
+        for partition in ["gcp", "aws-", "awsx"] {
+            let s = format!("arn:{partition}:ec2:us-east-1:123456789012:instance/i-0123");
+            assert_eq!(
+                ResourceName::parse_arn(&s).unwrap_err(),
+                ResourceNameError::InvalidArnPartition {
+                    span: Span::new(4, 4 + partition.len())
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn parse_arn_with_invalid_prefix() {
+        let s = "mrn:aws:ec2:us-east-1:123456789012:instance/i-0123";
+        assert_eq!(
+            ResourceName::parse_arn(s).unwrap_err(),
+            ResourceNameError::InvalidArnPrefix {
+                span: Span::new(0, 3)
+            }
+        );
+    }
+
+    #[test]
+    fn parse_arn_with_invalid_number_of_segments() {
+        let s = "arn:aws:sqs:us-east-1";
+        assert_eq!(
+            ResourceName::parse_arn(s).unwrap_err(),
+            ResourceNameError::InvalidSegmentCount {
+                expected: SEGMENT_COUNT,
+                found: 4,
+                span: Span::at(21),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_arn_with_empty_resource_parts() {
+        assert_eq!(
+            ResourceName::parse_arn("arn:aws:sqs:us-east-1:123456789012:").unwrap_err(),
+            ResourceNameError::EmptyQualifiedResourcePath { span: Span::at(35) }
+        );
+        assert_eq!(
+            ResourceName::parse_arn("arn:aws:iam::123456789012:/name").unwrap_err(),
+            ResourceNameError::EmptyResourceType { span: Span::at(26) }
+        );
+        assert_eq!(
+            ResourceName::parse_arn("arn:aws:iam::123456789012:role/").unwrap_err(),
+            ResourceNameError::EmptyResourcePath { span: Span::at(31) }
+        );
+    }
+}
@@ -0,0 +1,293 @@
+use alloc::collections::BTreeMap;
+#[cfg(feature = "std")]
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
+
+use crate::{ResourceName, ResourceNameError, Span};
+
+/// terminates each resource name.
+const NEWLINE: u8 = b'\n';
+
+/// precedes the newline of windows line endings.
+const CARRIAGE_RETURN: u8 = b'\r';
+
+/// a 1-based line number, and the result of parsing that line. error spans
+/// are relative to the line, not the whole input.
+pub type ParsedLine<'a> = (usize, Result<ResourceName<'a>, ResourceNameError>);
+
+/// parse a single line, without its '\n' (but possibly with a trailing '\r').
+/// bytes which aren't utf-8 are reported as `InvalidCharacter`.
+fn parse_line(line: &[u8]) -> Result<ResourceName<'_>, ResourceNameError> {
+    let line = line.strip_suffix(&[CARRIAGE_RETURN]).unwrap_or(line);
+    match core::str::from_utf8(line) {
+        Ok(line) => ResourceName::parse(line),
+        Err(err) => {
+            let start = err.valid_up_to();
+            let end = err.error_len().map_or(line.len(), |len| start + len);
+            Err(ResourceNameError::InvalidCharacter {
+                span: Span::new(start, end),
+            })
+        }
+    }
+}
+
+/// counts of the lines seen by a bulk parser, and of its errors grouped by
+/// `ResourceNameError::kind`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ErrorStats {
+    lines: usize,
+    errors: usize,
+    by_kind: BTreeMap<&'static str, usize>,
+}
+
+impl ErrorStats {
+    /// empty statistics.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// the number of lines parsed.
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    /// the number of lines which failed to parse.
+    pub fn errors(&self) -> usize {
+        self.errors
+    }
+
+    /// the number of errors of `kind`, e.g. `"EmptyService"`.
+    pub fn count(&self, kind: &str) -> usize {
+        self.by_kind.get(kind).copied().unwrap_or(0)
+    }
+
+    /// every error kind seen, and how often, ordered by kind.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, usize)> + '_ {
+        self.by_kind.iter().map(|(kind, count)| (*kind, *count))
+    }
+
+    /// record the outcome of parsing a line. only the first error of each
+    /// kind allocates.
+    fn record(&mut self, result: &Result<ResourceName<'_>, ResourceNameError>) {
+        self.lines += 1;
+        if let Err(err) = result {
+            self.errors += 1;
+            *self.by_kind.entry(err.kind()).or_default() += 1;
+        }
+    }
+}
+
+/// an iterator over the newline-delimited resource names of a byte slice,
+/// created via `ResourceName::parse_lines`.
+///
+/// both '\n' and '\r\n' line endings are accepted, and a final line ending is
+/// optional. empty lines are reported as `ResourceNameError::Empty`.
+#[derive(Debug, Clone)]
+pub struct ParseLines<'a> {
+    input: &'a [u8],
+    line_number: usize,
+    stats: Option<ErrorStats>,
+}
+
+impl ParseLines<'_> {
+    /// collect `ErrorStats` while parsing.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(ErrorStats::new());
+        self
+    }
+
+    /// the statistics so far, if they're being collected.
+    pub fn stats(&self) -> Option<&ErrorStats> {
+        self.stats.as_ref()
+    }
+}
+
+impl<'a> Iterator for ParseLines<'a> {
+    type Item = ParsedLine<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.input.is_empty() {
+            return None;
+        }
+
+        let (line, rest) = match self.input.iter().position(|byte| *byte == NEWLINE) {
+            Some(end) => (&self.input[..end], &self.input[end + 1..]),
+            None => (self.input, &self.input[self.input.len()..]),
+        };
+        self.input = rest;
+        self.line_number += 1;
+
+        let result = parse_line(line);
+        if let Some(stats) = &mut self.stats {
+            stats.record(&result);
+        }
+        Some((self.line_number, result))
+    }
+}
+
+impl core::iter::FusedIterator for ParseLines<'_> {}
+
+/// reads newline-delimited resource names from a `BufRead`, created via
+/// `ResourceName::read_lines`.
+///
+/// every line is read into the same buffer, so only lines longer than any
+/// before them allocate. each parsed name borrows that buffer, which is why
+/// this is `ReadLines::next_line` rather than an `Iterator`.
+///
+/// line endings and empty lines are handled as in `ParseLines`.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ReadLines<R> {
+    reader: R,
+    buf: Vec<u8>,
+    line_number: usize,
+    stats: Option<ErrorStats>,
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> ReadLines<R> {
+    /// collect `ErrorStats` while parsing.
+    pub fn with_stats(mut self) -> Self {
+        self.stats = Some(ErrorStats::new());
+        self
+    }
+
+    /// the statistics so far, if they're being collected.
+    pub fn stats(&self) -> Option<&ErrorStats> {
+        self.stats.as_ref()
+    }
+
+    /// read and parse the next line, `None` once the reader is exhausted.
+    pub fn next_line(&mut self) -> Option<io::Result<ParsedLine<'_>>> {
+        self.buf.clear();
+        match self.reader.read_until(NEWLINE, &mut self.buf) {
+            Ok(0) => return None,
+            Ok(_) => {}
+            Err(err) => return Some(Err(err)),
+        }
+        self.line_number += 1;
+
+        let line = self.buf.strip_suffix(&[NEWLINE]).unwrap_or(&self.buf);
+        let result = parse_line(line);
+        if let Some(stats) = &mut self.stats {
+            stats.record(&result);
+        }
+        Some(Ok((self.line_number, result)))
+    }
+
+    /// the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+/// bulk parsing, for importing inventories of one resource name per line.
+impl ResourceName<'_> {
+    /// parse every line of `input` as a resource name, without allocating.
+    pub fn parse_lines(input: &[u8]) -> ParseLines<'_> {
+        ParseLines {
+            input,
+            line_number: 0,
+            stats: None,
+        }
+    }
+
+    /// parse every line read from `reader` as a resource name, reusing a
+    /// single line buffer.
+    #[cfg(feature = "std")]
+    pub fn read_lines<R: BufRead>(reader: R) -> ReadLines<R> {
+        ReadLines {
+            reader,
+            buf: Vec::new(),
+            line_number: 0,
+            stats: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    const INVENTORY: &[u8] = b"mrn:tycho:opa:::member/anderson-dawes\n\
+        mrn:tycho::::member/naomi-nagata\r\n\
+        \n\
+        mrn:tycho:opa:::station/\xffceres\n\
+        mrn:tycho:opa:::station/ceres/bay-12";
+
+    fn expected() -> Vec<ParsedLine<'static>> {
+        Vec::from([
+            (
+                1,
+                Ok(ResourceName::parse("mrn:tycho:opa:::member/anderson-dawes").unwrap()),
+            ),
+            (
+                2,
+                Err(ResourceNameError::EmptyService { span: Span::at(10) }),
+            ),
+            (3, Err(ResourceNameError::Empty)),
+            (
+                4,
+                Err(ResourceNameError::InvalidCharacter {
+                    span: Span::new(24, 25),
+                }),
+            ),
+            (
+                5,
+                Ok(ResourceName::parse("mrn:tycho:opa:::station/ceres/bay-12").unwrap()),
+            ),
+        ])
+    }
+
+    #[test]
+    fn parse_lines() {
+        assert_eq!(
+            ResourceName::parse_lines(INVENTORY).collect::<Vec<_>>(),
+            expected()
+        );
+    }
+
+    #[test]
+    fn parse_lines_ignores_a_final_newline() {
+        let lines: Vec<_> =
+            ResourceName::parse_lines(b"mrn:tycho:opa:::member/anderson-dawes\n").collect();
+        assert_eq!(lines.len(), 1);
+        assert!(ResourceName::parse_lines(b"").next().is_none());
+    }
+
+    #[test]
+    fn parse_lines_with_stats() {
+        let mut lines = ResourceName::parse_lines(INVENTORY).with_stats();
+        lines.by_ref().for_each(drop);
+
+        let stats = lines.stats().unwrap();
+        assert_eq!(stats.lines(), 5);
+        assert_eq!(stats.errors(), 3);
+        assert_eq!(stats.count("EmptyService"), 1);
+        assert_eq!(stats.count("InvalidCharacter"), 1);
+        assert_eq!(stats.count("InvalidUrn"), 0);
+        assert_eq!(
+            stats.iter().collect::<Vec<_>>(),
+            [("Empty", 1), ("EmptyService", 1), ("InvalidCharacter", 1)]
+        );
+        assert!(ResourceName::parse_lines(INVENTORY).stats().is_none());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn read_lines_agrees_with_parse_lines() {
+        // a tiny buffer, so lines straddle reads
+        let reader = std::io::BufReader::with_capacity(4, INVENTORY);
+        let mut lines = ResourceName::read_lines(reader).with_stats();
+
+        let mut expected = expected().into_iter();
+        while let Some(line) = lines.next_line() {
+            assert_eq!(line.unwrap(), expected.next().unwrap());
+        }
+        assert!(expected.next().is_none());
+        assert_eq!(lines.stats().unwrap().errors(), 3);
+    }
+}
@@ -0,0 +1,348 @@
+use alloc::string::String;
+
+use unicode_normalization::{UnicodeNormalization, is_nfc, is_nfkc};
+
+use crate::resource_name::{RESOURCE_PATH_SEGMENT_SEPARATOR, SEGMENT_SEPARATOR};
+use crate::{ResourceName, ResourceNameBuf, ResourceNameError, Span};
+
+/// invisible formatting and bidi control characters. they render as nothing
+/// (or re-order what's around them), which makes two names that _look_
+/// identical compare differently.
+const INVISIBLE_CHARACTERS: &[(char, char)] = &[
+    ('\u{00AD}', '\u{00AD}'),   // soft hyphen
+    ('\u{061C}', '\u{061C}'),   // arabic letter mark
+    ('\u{180E}', '\u{180E}'),   // mongolian vowel separator
+    ('\u{200B}', '\u{200F}'),   // zero width space, joiners, lrm/rlm
+    ('\u{202A}', '\u{202E}'),   // bidi embeddings and overrides
+    ('\u{2060}', '\u{2064}'),   // word joiner, invisible operators
+    ('\u{2066}', '\u{206F}'),   // bidi isolates, deprecated formatting
+    ('\u{FEFF}', '\u{FEFF}'),   // zero width no-break space
+    ('\u{FFF9}', '\u{FFFB}'),   // interlinear annotations
+    ('\u{E0000}', '\u{E007F}'), // tags
+];
+
+/// scripts whose letters are easily mistaken for one another, e.g. latin
+/// 'a', cyrillic 'а' and greek 'α'.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+}
+
+impl Script {
+    fn of(c: char) -> Option<Self> {
+        match c {
+            'a'..='z'
+            | 'A'..='Z'
+            | '\u{00C0}'..='\u{024F}'
+            | '\u{1E00}'..='\u{1EFF}'
+            | '\u{FF21}'..='\u{FF3A}'
+            | '\u{FF41}'..='\u{FF5A}' => Some(Script::Latin),
+            '\u{0370}'..='\u{03FF}' | '\u{1F00}'..='\u{1FFF}' => Some(Script::Greek),
+            '\u{0400}'..='\u{052F}'
+            | '\u{1C80}'..='\u{1C8F}'
+            | '\u{2DE0}'..='\u{2DFF}'
+            | '\u{A640}'..='\u{A69F}' => Some(Script::Cyrillic),
+            _ => None,
+        }
+    }
+}
+
+/// how a segment is folded into its canonical form.
+#[derive(Debug, Clone, Copy)]
+enum Folding {
+    /// structural identifiers ({prefix}, {partition}, {service}, {region},
+    /// {resource_type}): NFKC and lowercase, so e.g. 'ＯＰＡ' and 'opa' are the
+    /// same service.
+    Identifier,
+    /// case-sensitive values ({account_id}, {resource_path}): NFC only, since
+    /// ids such as ksuids are case-sensitive.
+    Text,
+}
+
+/// canonicalization, for when resource names are compared as a security
+/// boundary.
+///
+/// a canonical resource name has:
+/// - no control, invisible formatting or bidi control characters.
+/// - {prefix}, {partition}, {service}, {region} and {resource_type} in NFKC,
+///   lowercase form.
+/// - {account_id} and {resource_path} in NFC form.
+/// - no identifier segment mixing latin with greek or cyrillic letters, e.g.
+///   'оpa' with a cyrillic 'о'.
+///
+/// note: compatibility forms (e.g. fullwidth 'ａ') are folded away, but an
+/// identifier written wholly in another script (e.g. cyrillic 'ора') is taken
+/// at its word, as is an {account_id} or {resource_path} in any mix of
+/// scripts.
+impl<'a> ResourceName<'a> {
+    /// parse a `ResourceName`, rejecting input which is not already in
+    /// canonical form. like `ResourceName::parse`, nothing is allocated.
+    pub fn parse_strict(input: &'a str) -> Result<Self, ResourceNameError> {
+        let rn = ResourceName::parse(input)?;
+        rn.verify_canonical()?;
+        Ok(rn)
+    }
+
+    /// verify this resource name is in canonical form. error spans are
+    /// relative to the `Display` form of the name.
+    pub fn verify_canonical(&self) -> Result<(), ResourceNameError> {
+        for (segment, folding, offset) in self.foldable_segments() {
+            reject_invalid_characters(segment, offset)?;
+            if let Folding::Identifier = folding {
+                reject_mixed_scripts(segment, offset)?;
+            }
+
+            let canonical = match folding {
+                Folding::Identifier => {
+                    is_nfkc(segment) && segment.chars().all(|c| c.to_lowercase().eq([c]))
+                }
+                Folding::Text => is_nfc(segment),
+            };
+            if !canonical {
+                return Err(ResourceNameError::NonCanonical {
+                    span: Span::new(offset, offset + segment.len()),
+                });
+            }
+        }
+
+        Ok(())
+    }
+
+    /// fold this resource name into its canonical form. error spans are
+    /// relative to the `Display` form of the name.
+    pub fn canonicalize(&self) -> Result<ResourceNameBuf, ResourceNameError> {
+        let mut folded: [String; 7] = Default::default();
+        for ((segment, folding, offset), folded) in
+            self.foldable_segments().into_iter().zip(folded.iter_mut())
+        {
+            reject_invalid_characters(segment, offset)?;
+            if let Folding::Identifier = folding {
+                reject_mixed_scripts(segment, offset)?;
+            }
+
+            *folded = match folding {
+                Folding::Identifier => segment.nfkc().flat_map(char::to_lowercase).nfkc().collect(),
+                Folding::Text => segment.nfc().collect(),
+            };
+
+            // compatibility folding can conjure separators out of thin air
+            // (e.g. fullwidth '：'), which would change the shape of the name.
+            for separator in [SEGMENT_SEPARATOR, RESOURCE_PATH_SEGMENT_SEPARATOR] {
+                if folded.matches(separator).count() != segment.matches(separator).count() {
+                    return Err(ResourceNameError::InvalidCharacter {
+                        span: Span::new(offset, offset + segment.len()),
+                    });
+                }
+            }
+        }
+
+        Ok(ResourceNameBuf::from(&ResourceName {
+            prefix: &folded[0],
+            partition: &folded[1],
+            service: &folded[2],
+            region: &folded[3],
+            account_id: &folded[4],
+            resource_type: &folded[5],
+            resource_separator: self.resource_separator,
+            resource_path: &folded[6],
+        }))
+    }
+
+    /// every segment, how it folds, and its byte offset within the `Display`
+    /// form of the name.
+    fn foldable_segments(&self) -> [(&'a str, Folding, usize); 7] {
+        let mut segments = [
+            (self.prefix, Folding::Identifier, 0),
+            (self.partition, Folding::Identifier, 0),
+            (self.service, Folding::Identifier, 0),
+            (self.region, Folding::Identifier, 0),
+            (self.account_id, Folding::Text, 0),
+            (self.resource_type, Folding::Identifier, 0),
+            (self.resource_path, Folding::Text, 0),
+        ];
+
+        let mut offset = 0;
+        for (idx, (segment, _, segment_offset)) in segments.iter_mut().enumerate() {
+            *segment_offset = offset;
+            offset += segment.len();
+            offset += match idx {
+                // {resource_type} is followed by the resource separator
+                5 => self.resource_separator.len(),
+                _ => SEGMENT_SEPARATOR.len(),
+            };
+        }
+
+        segments
+    }
+}
+
+/// reject control and invisible characters, `offset` is where `segment`
+/// begins within the whole name.
+fn reject_invalid_characters(segment: &str, offset: usize) -> Result<(), ResourceNameError> {
+    let invalid = segment.char_indices().find(|(_, c)| {
+        c.is_control()
+            || INVISIBLE_CHARACTERS
+                .iter()
+                .any(|(start, end)| (*start..=*end).contains(c))
+    });
+
+    match invalid {
+        Some((idx, c)) => Err(ResourceNameError::InvalidCharacter {
+            span: Span::new(offset + idx, offset + idx + c.len_utf8()),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// reject an identifier mixing latin with greek or cyrillic letters, the
+/// usual shape of a spoofed name. `offset` is where `segment` begins within
+/// the whole name.
+fn reject_mixed_scripts(segment: &str, offset: usize) -> Result<(), ResourceNameError> {
+    let mut scripts = segment
+        .char_indices()
+        .filter_map(|(idx, c)| Script::of(c).map(|script| (idx, c, script)));
+    let Some((_, _, first)) = scripts.next() else {
+        return Ok(());
+    };
+
+    match scripts.find(|(_, _, script)| *script != first) {
+        Some((idx, c, _)) => Err(ResourceNameError::MixedScript {
+            span: Span::new(offset + idx, offset + idx + c.len_utf8()),
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_RESOURCE_NAME: &str =
+        "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes";
+
+    #[test]
+    fn canonical_names_are_untouched() {
+        let rn = ResourceName::parse_strict(VALID_RESOURCE_NAME).unwrap();
+        assert_eq!(rn.canonicalize().unwrap().as_str(), VALID_RESOURCE_NAME);
+    }
+
+    #[test]
+    fn nfc_and_nfd_paths_are_the_same_resource() {
+        let nfc = "mrn:tycho:opa:::member/jos\u{e9}";
+        let nfd = "mrn:tycho:opa:::member/jose\u{301}";
+
+        let nfc = ResourceName::parse(nfc).unwrap();
+        let nfd = ResourceName::parse(nfd).unwrap();
+        assert_ne!(nfc, nfd);
+        assert_eq!(nfc.canonicalize().unwrap(), nfd.canonicalize().unwrap());
+    }
+
+    #[test]
+    fn identifiers_are_folded_but_ids_keep_their_case() {
+        let rn = ResourceName::parse(
+            "MRN:Tycho:\u{ff2f}\u{ff30}\u{ff21}:Sol-Belt-1:36UeVtK7:Member/Anderson-Dawes",
+        )
+        .unwrap();
+        assert_eq!(
+            rn.canonicalize().unwrap().as_str(),
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7:member/Anderson-Dawes"
+        );
+    }
+
+    #[test]
+    fn parse_strict_rejects_non_canonical_input() {
+        let s = "mrn:tycho:OPA:sol-belt-1:36UeVtK7:member/anderson-dawes";
+        assert_eq!(
+            ResourceName::parse_strict(s).unwrap_err(),
+            ResourceNameError::NonCanonical {
+                span: Span::new(10, 13)
+            }
+        );
+
+        let s = "mrn:tycho:opa:::member/jose\u{301}";
+        assert_eq!(
+            ResourceName::parse_strict(s).unwrap_err(),
+            ResourceNameError::NonCanonical {
+                span: Span::new(23, 29)
+            }
+        );
+    }
+
+    #[test]
+    fn invisible_and_control_characters_are_rejected() {
+        let s = "mrn:tycho:opa:::member/anderson\u{200b}-dawes";
+        let err = ResourceName::parse_strict(s).unwrap_err();
+        assert_eq!(
+            err,
+            ResourceNameError::InvalidCharacter {
+                span: Span::new(31, 34)
+            }
+        );
+        assert_eq!(
+            ResourceName::parse(s).unwrap().canonicalize().unwrap_err(),
+            err
+        );
+
+        let s = "mrn:tycho:opa:::member/anderson\n-dawes";
+        assert_eq!(
+            ResourceName::parse_strict(s).unwrap_err(),
+            ResourceNameError::InvalidCharacter {
+                span: Span::new(31, 32)
+            }
+        );
+    }
+
+    #[test]
+    fn mixed_script_identifiers_are_rejected() {
+        // a cyrillic 'о' (U+043E) in place of the latin 'o' of "opa"
+        let s = "mrn:tycho:\u{43e}pa:::member/anderson-dawes";
+        let err = ResourceName::parse_strict(s).unwrap_err();
+        assert_eq!(
+            err,
+            ResourceNameError::MixedScript {
+                span: Span::new(12, 13)
+            }
+        );
+        assert_eq!(
+            ResourceName::parse(s).unwrap().canonicalize().unwrap_err(),
+            ResourceNameError::MixedScript {
+                span: Span::new(12, 13)
+            }
+        );
+
+        // greek in a resource type
+        let s = "mrn:tycho:opa:::\u{3bc}ember/anderson-dawes";
+        assert!(matches!(
+            ResourceName::parse_strict(s).unwrap_err(),
+            ResourceNameError::MixedScript { .. }
+        ));
+    }
+
+    #[test]
+    fn single_script_segments_are_allowed() {
+        // wholly cyrillic identifiers, and mixed scripts in case-sensitive
+        // values, are left alone
+        let s = "mrn:tycho:\u{43e}\u{440}\u{430}:::member/anderson-\u{434}awes";
+        assert!(ResourceName::parse_strict(s).is_ok());
+        // fullwidth latin folds to latin, so isn't mixed
+        let rn = ResourceName::parse("mrn:tycho:o\u{ff30}a:::member/anderson-dawes").unwrap();
+        assert_eq!(
+            rn.canonicalize().unwrap().as_str(),
+            "mrn:tycho:opa:::member/anderson-dawes"
+        );
+    }
+
+    #[test]
+    fn folding_may_not_introduce_separators() {
+        // fullwidth colon folds to ':' under NFKC
+        let s = "mrn:tycho:opa\u{ff1a}x:::member/anderson-dawes";
+        assert_eq!(
+            ResourceName::parse(s).unwrap().canonicalize().unwrap_err(),
+            ResourceNameError::InvalidCharacter {
+                span: Span::new(10, 17)
+            }
+        );
+    }
+}
@@ -0,0 +1,168 @@
+use core::fmt::Write;
+
+use unicode_width::UnicodeWidthChar;
+
+use crate::ResourceNameError;
+
+/// the marker drawn beneath offending input.
+const UNDERLINE: char = '^';
+
+/// the indentation applied to the input and underline.
+const INDENT: &str = "  ";
+
+/// a human friendly rendering of a `ResourceNameError`, created via
+/// `ResourceNameError::render`.
+///
+/// the output is plain text (no colors, no trailing newline), so it's equally
+/// happy in a terminal or an API error body:
+///
+/// ```text
+/// missing service in resource name
+///   mrn:tycho::sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes
+///             ^
+/// ```
+///
+/// columns are counted in display width rather than bytes (e.g. `ダ` is two
+/// columns wide, a combining accent none), and control characters are shown
+/// escaped (e.g. a newline as `\n`), so the underline stays aligned beneath
+/// the input.
+#[derive(Debug, Clone, Copy)]
+pub struct Diagnostic<'a> {
+    error: &'a ResourceNameError,
+    input: &'a str,
+}
+
+impl<'a> Diagnostic<'a> {
+    pub(crate) fn new(error: &'a ResourceNameError, input: &'a str) -> Self {
+        Diagnostic { error, input }
+    }
+
+    /// the column at which the underline begins, and its width (at least 1).
+    fn columns(&self) -> (usize, usize) {
+        let span = self.error.span();
+
+        // a span which doesn't line up with the input (e.g. it was rendered
+        // against the wrong string) gets clamped rather than panicking.
+        let clamp = |offset: usize| {
+            let mut offset = offset.min(self.input.len());
+            while !self.input.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            offset
+        };
+        let (start, end) = (clamp(span.start), clamp(span.end));
+
+        let columns = |text: &str| text.chars().map(display_width).sum::<usize>();
+        let column = columns(&self.input[..start]);
+        let width = columns(&self.input[start..end]).max(1);
+        (column, width)
+    }
+}
+
+impl core::fmt::Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let (column, width) = self.columns();
+
+        writeln!(f, "{}", self.error)?;
+        f.write_str(INDENT)?;
+        for c in self.input.chars() {
+            if c.is_control() {
+                write!(f, "{}", c.escape_default())?;
+            } else {
+                f.write_char(c)?;
+            }
+        }
+        writeln!(f)?;
+        write!(f, "{INDENT}{:column$}", "")?;
+        for _ in 0..width {
+            write!(f, "{UNDERLINE}")?;
+        }
+        Ok(())
+    }
+}
+
+/// how many columns `c` takes up once rendered, control characters being
+/// escaped.
+fn display_width(c: char) -> usize {
+    if c.is_control() {
+        c.escape_default().count()
+    } else {
+        c.width().unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use crate::{ResourceName, ResourceNameSchema};
+
+    #[test]
+    fn render_missing_segment() {
+        let input = "mrn:tycho::sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes";
+        let err = ResourceName::parse(input).unwrap_err();
+        assert_eq!(
+            err.render(input).to_string(),
+            "missing service in resource name\n\
+             \x20 mrn:tycho::sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes\n\
+             \x20           ^"
+        );
+    }
+
+    #[test]
+    fn render_extra_segments() {
+        let input = "mrn:tycho:opa:sol-belt-1:36UeVtK7:member/anderson-dawes:extra";
+        let err = ResourceName::parse(input).unwrap_err();
+        assert_eq!(
+            err.render(input).to_string(),
+            "invalid resource name segment count: expected 6 segments, found 7\n\
+             \x20 mrn:tycho:opa:sol-belt-1:36UeVtK7:member/anderson-dawes:extra\n\
+             \x20                                                        ^^^^^^"
+        );
+    }
+
+    #[test]
+    fn render_counts_display_width_not_bytes() {
+        let input = "mrn:ティコ::sol-belt-1:36UeVtK7:member/anderson-dawes";
+        let err = ResourceNameSchema::DEFAULT.parse(input).unwrap_err();
+        assert_eq!(
+            err.render(input).to_string(),
+            "missing service in resource name\n\
+             \x20 mrn:ティコ::sol-belt-1:36UeVtK7:member/anderson-dawes\n\
+             \x20            ^"
+        );
+
+        let input = "mrn:e\u{301}x::c:d:x/y";
+        let err = ResourceName::parse(input).unwrap_err();
+        assert_eq!(
+            err.render(input).to_string(),
+            "missing service in resource name\n\
+             \x20 mrn:e\u{301}x::c:d:x/y\n\
+             \x20        ^"
+        );
+    }
+
+    #[test]
+    fn render_escapes_control_characters() {
+        let input = "mrn:tycho:opa:::member/x\ny";
+        let err = ResourceName::parse(input).unwrap_err();
+        assert_eq!(
+            err.render(input).to_string(),
+            "invalid character in resource name\n\
+             \x20 mrn:tycho:opa:::member/x\\ny\n\
+             \x20                         ^^"
+        );
+    }
+
+    #[test]
+    fn render_against_mismatched_input() {
+        let err = ResourceName::parse("mrn:tycho:opa:sol-belt-1:36UeVtK7:member/anderson-dawes:x")
+            .unwrap_err();
+        assert_eq!(
+            err.render("short").to_string(),
+            "invalid resource name segment count: expected 6 segments, found 7\n\
+             \x20 short\n\
+             \x20      ^"
+        );
+    }
+}
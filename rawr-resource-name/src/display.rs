@@ -0,0 +1,250 @@
+use crate::ResourceName;
+use crate::resource_name::RESOURCE_PATH_SEGMENT_SEPARATOR;
+
+/// marks a segment whose value has been replaced by its hash.
+const REDACTED_MARKER: char = '#';
+
+/// a secret key for `ResourceName::redacted`.
+///
+/// redacted values are hashed with SipHash-2-4 under this key, so without it
+/// they can't be recovered by hashing candidate values (e.g. every 12 digit
+/// account id). the same key always gives the same output, so names can be
+/// correlated across logs for as long as the key is kept.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RedactionKey {
+    k0: u64,
+    k1: u64,
+}
+
+impl RedactionKey {
+    /// a key from 16 secret bytes, e.g. loaded from a secret store. the bytes
+    /// should be random, a guessable key is no better than none.
+    pub const fn new(bytes: [u8; 16]) -> Self {
+        let [a, b, c, d, e, f, g, h, i, j, k, l, m, n, o, p] = bytes;
+        RedactionKey {
+            k0: u64::from_le_bytes([a, b, c, d, e, f, g, h]),
+            k1: u64::from_le_bytes([i, j, k, l, m, n, o, p]),
+        }
+    }
+
+    /// SipHash-2-4 of `value`.
+    fn hash(&self, value: &[u8]) -> u64 {
+        let mut state = [
+            self.k0 ^ 0x736f_6d65_7073_6575,
+            self.k1 ^ 0x646f_7261_6e64_6f6d,
+            self.k0 ^ 0x6c79_6765_6e65_7261,
+            self.k1 ^ 0x7465_6462_7974_6573,
+        ];
+        let mut compress = |word: u64, rounds: usize| {
+            state[3] ^= word;
+            (0..rounds).for_each(|_| sip_round(&mut state));
+            state[0] ^= word;
+        };
+
+        let mut chunks = value.chunks_exact(8);
+        for chunk in chunks.by_ref() {
+            compress(
+                u64::from_le_bytes(chunk.try_into().expect("chunk is a word")),
+                2,
+            );
+        }
+        // the final word holds the remaining bytes and the length's low byte
+        let mut last = [0; 8];
+        last[..chunks.remainder().len()].copy_from_slice(chunks.remainder());
+        last[7] = value.len() as u8;
+        compress(u64::from_le_bytes(last), 2);
+
+        state[2] ^= 0xff;
+        (0..4).for_each(|_| sip_round(&mut state));
+        state.iter().fold(0, |hash, v| hash ^ v)
+    }
+}
+
+/// the key is secret, so never shows up in debug output.
+impl core::fmt::Debug for RedactionKey {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str("RedactionKey(..)")
+    }
+}
+
+fn sip_round([v0, v1, v2, v3]: &mut [u64; 4]) {
+    *v0 = v0.wrapping_add(*v1);
+    *v1 = v1.rotate_left(13) ^ *v0;
+    *v0 = v0.rotate_left(32);
+    *v2 = v2.wrapping_add(*v3);
+    *v3 = v3.rotate_left(16) ^ *v2;
+    *v0 = v0.wrapping_add(*v3);
+    *v3 = v3.rotate_left(21) ^ *v0;
+    *v2 = v2.wrapping_add(*v1);
+    *v1 = v1.rotate_left(17) ^ *v2;
+    *v2 = v2.rotate_left(32);
+}
+
+/// write `value` masked by its hash, empty values have nothing to hide.
+fn write_masked(
+    f: &mut core::fmt::Formatter<'_>,
+    key: &RedactionKey,
+    value: &str,
+) -> core::fmt::Result {
+    if value.is_empty() {
+        return Ok(());
+    }
+    write!(f, "{REDACTED_MARKER}{:016x}", key.hash(value.as_bytes()))
+}
+
+/// a `ResourceName` with its {account_id} and {resource_path} replaced by a
+/// keyed hash, created via `ResourceName::redacted`.
+///
+/// ```text
+/// mrn:tycho:opa:sol-belt-1:#c391b5bc443d62a3:member/#7e54be8f0755e3b3
+/// ```
+///
+/// the same name and key always redact to the same output, so requests can
+/// still be correlated. see `RedactionKey`.
+#[derive(Debug, Clone, Copy)]
+pub struct Redacted<'a> {
+    name: ResourceName<'a>,
+    key: RedactionKey,
+}
+
+impl core::fmt::Display for Redacted<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let rn = &self.name;
+        write!(
+            f,
+            "{}:{}:{}:{}:",
+            rn.prefix, rn.partition, rn.service, rn.region
+        )?;
+        write_masked(f, &self.key, rn.account_id)?;
+        write!(f, ":{}{}", rn.resource_type, rn.resource_separator)?;
+        write_masked(f, &self.key, rn.resource_path)
+    }
+}
+
+/// an abbreviated `ResourceName`, `{service}:{resource_type}/{last segment}`,
+/// created via `ResourceName::short`.
+///
+/// ```text
+/// opa:station/bay-12
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Short<'a> {
+    name: ResourceName<'a>,
+}
+
+impl core::fmt::Display for Short<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let rn = &self.name;
+        let last_segment = rn
+            .resource_path
+            .rsplit(RESOURCE_PATH_SEGMENT_SEPARATOR)
+            .next()
+            .unwrap_or(rn.resource_path);
+        write!(
+            f,
+            "{}:{}{}{}",
+            rn.service, rn.resource_type, rn.resource_separator, last_segment
+        )
+    }
+}
+
+/// alternate display modes, for logging and audit sinks which shouldn't see
+/// tenant identifiers.
+impl<'a> ResourceName<'a> {
+    /// display this resource name with its {account_id} and {resource_path}
+    /// masked by their hash under `key`.
+    pub fn redacted(&self, key: &RedactionKey) -> Redacted<'a> {
+        Redacted {
+            name: *self,
+            key: *key,
+        }
+    }
+
+    /// display this resource name as `{service}:{resource_type}/{last
+    /// segment}`.
+    pub fn short(&self) -> Short<'a> {
+        Short { name: *self }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::format;
+    use alloc::string::ToString;
+
+    use super::*;
+
+    const VALID_RESOURCE_NAME: &str =
+        "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes";
+
+    /// the key of the SipHash reference vectors.
+    const KEY: RedactionKey =
+        RedactionKey::new([0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15]);
+
+    #[test]
+    fn hash_matches_siphash_reference_vectors() {
+        assert_eq!(KEY.hash(b""), 0x726f_db47_dd0e_0e31);
+        let message: [u8; 15] = core::array::from_fn(|idx| idx as u8);
+        assert_eq!(KEY.hash(&message), 0xa129_ca61_49be_45e5);
+    }
+
+    #[test]
+    fn redacted_masks_account_and_path() {
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME).unwrap();
+        let redacted = rn.redacted(&KEY).to_string();
+        assert_eq!(
+            redacted,
+            "mrn:tycho:opa:sol-belt-1:#c391b5bc443d62a3:member/#7e54be8f0755e3b3"
+        );
+
+        // still a resource name, with the identifiers gone
+        let parsed = ResourceName::parse(&redacted).unwrap();
+        assert_eq!(parsed.service(), "opa");
+        assert!(!redacted.contains(rn.account_id()));
+        assert!(!redacted.contains(rn.resource_path()));
+    }
+
+    #[test]
+    fn redacted_is_stable_and_keeps_global_names_global() {
+        let rn = ResourceName::parse("mrn:tycho:opa:::station/ceres/bay-12").unwrap();
+        assert_eq!(
+            rn.redacted(&KEY).to_string(),
+            "mrn:tycho:opa:::station/#a32e9b141fa9cc06"
+        );
+    }
+
+    #[test]
+    fn redacted_depends_on_the_key() {
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME).unwrap();
+        let other = RedactionKey::new([0xA5; 16]);
+        assert_ne!(
+            rn.redacted(&KEY).to_string(),
+            rn.redacted(&other).to_string()
+        );
+        assert_eq!(format!("{KEY:?}"), "RedactionKey(..)");
+        assert!(!format!("{:?}", rn.redacted(&KEY)).contains("k0"));
+    }
+
+    #[test]
+    fn short_keeps_the_last_path_segment() {
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME).unwrap();
+        assert_eq!(rn.short().to_string(), "opa:member/anderson-dawes");
+
+        let rn = ResourceName::parse(
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:station/ceres/bay-12",
+        )
+        .unwrap();
+        assert_eq!(rn.short().to_string(), "opa:station/bay-12");
+    }
+
+    #[test]
+    fn adapters_respect_arn_resource_separators() {
+        let rn = ResourceName::parse_arn("arn:aws:lambda:us-east-1:123456789012:function:my-fn")
+            .unwrap();
+        assert_eq!(rn.short().to_string(), "lambda:function:my-fn");
+        assert_eq!(
+            rn.redacted(&KEY).to_string(),
+            "arn:aws:lambda:us-east-1:#e839790731eaba2f:function:#ad9fb9423fbb1b9b"
+        );
+    }
+}
@@ -1,7 +1,60 @@
 use thiserror::Error;
 
+use crate::diagnostic::Diagnostic;
+
+/// a half-open range of byte offsets into the input which caused an error.
+///
+/// an empty span (`start == end`) points _between_ two bytes, which is where
+/// a missing segment would have been.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    /// a span covering `start..end`.
+    pub const fn new(start: usize, end: usize) -> Self {
+        Span { start, end }
+    }
+
+    /// an empty span at `offset`.
+    pub const fn at(offset: usize) -> Self {
+        Span {
+            start: offset,
+            end: offset,
+        }
+    }
+
+    /// the span of `part` within `input`, where `part` must be a sub-slice of
+    /// `input` (e.g. a segment handed back by `str::split`).
+    pub(crate) fn of(input: &str, part: &str) -> Self {
+        let start = part.as_ptr() as usize - input.as_ptr() as usize;
+        Span::new(start, start + part.len())
+    }
+
+    /// the number of bytes covered by the span.
+    pub const fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// whether the span covers no bytes at all.
+    pub const fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+impl From<Span> for core::ops::Range<usize> {
+    fn from(span: Span) -> Self {
+        span.start..span.end
+    }
+}
+
 /// these are the possible errors that might occur when parsing or constructing
 /// a `ResourceName`.
+///
+/// every variant except `Empty` carries the `Span` of the input at fault, see
+/// `ResourceNameError::render` to point a human at it.
 // TODO(nick): at some point, we should probably make these a little less "yo
 // dawg" and a little more sad, er - professional. at some point, eventually.
 #[derive(Debug, Error, PartialEq)]
@@ -10,23 +63,164 @@ pub enum ResourceNameError {
     Empty,
 
     #[error("invalid resource name segment count: expected {expected} segments, found {found}")]
-    InvalidSegmentCount { expected: usize, found: usize },
+    InvalidSegmentCount {
+        expected: usize,
+        found: usize,
+        span: Span,
+    },
 
     #[error("missing resource name prefix")]
-    EmptyPrefix,
+    EmptyPrefix { span: Span },
 
     #[error("missing partition in resource name")]
-    EmptyPartition,
+    EmptyPartition { span: Span },
 
     #[error("missing service in resource name")]
-    EmptyService,
+    EmptyService { span: Span },
 
     #[error("missing qualified resource path")]
-    EmptyQualifiedResourcePath,
+    EmptyQualifiedResourcePath { span: Span },
 
     #[error("missing resource type")]
-    EmptyResourceType,
+    EmptyResourceType { span: Span },
 
     #[error("missing resource path")]
-    EmptyResourcePath,
+    EmptyResourcePath { span: Span },
+
+    #[error("invalid resource path segment")]
+    InvalidResourcePathSegment { span: Span },
+
+    #[error("missing {segment} in resource name")]
+    EmptySegment { segment: &'static str, span: Span },
+
+    #[error("resource name must begin with {expected:?}")]
+    InvalidLeader { expected: &'static str, span: Span },
+
+    #[error("missing 'arn' prefix")]
+    InvalidArnPrefix { span: Span },
+
+    #[error("invalid arn partition, expected 'aws' or 'aws-*'")]
+    InvalidArnPartition { span: Span },
+
+    #[error("invalid character in resource name")]
+    InvalidCharacter { span: Span },
+
+    #[error("resource name is not in canonical form")]
+    NonCanonical { span: Span },
+
+    #[error("identifier mixes latin with greek or cyrillic characters")]
+    MixedScript { span: Span },
+
+    #[error("invalid percent-encoding in resource name")]
+    InvalidPercentEncoding { span: Span },
+
+    #[error("not a rawr resource name urn")]
+    InvalidUrn { span: Span },
+
+    #[error("unterminated template variable, expected '}}'")]
+    UnterminatedVariable { span: Span },
+
+    #[error("invalid template variable name")]
+    InvalidVariable { span: Span },
+
+    #[error("unresolved template variable")]
+    UnresolvedVariable { span: Span },
+
+    #[error("template variable value is empty or contains a separator")]
+    InvalidVariableValue { span: Span },
+}
+
+impl ResourceNameError {
+    /// the byte span of the input at fault.
+    pub fn span(&self) -> Span {
+        match self {
+            ResourceNameError::Empty => Span::at(0),
+            ResourceNameError::InvalidSegmentCount { span, .. }
+            | ResourceNameError::EmptyPrefix { span }
+            | ResourceNameError::EmptyPartition { span }
+            | ResourceNameError::EmptyService { span }
+            | ResourceNameError::EmptyQualifiedResourcePath { span }
+            | ResourceNameError::EmptyResourceType { span }
+            | ResourceNameError::EmptyResourcePath { span }
+            | ResourceNameError::InvalidResourcePathSegment { span }
+            | ResourceNameError::EmptySegment { span, .. }
+            | ResourceNameError::InvalidLeader { span, .. }
+            | ResourceNameError::InvalidArnPrefix { span }
+            | ResourceNameError::InvalidArnPartition { span }
+            | ResourceNameError::InvalidCharacter { span }
+            | ResourceNameError::NonCanonical { span }
+            | ResourceNameError::MixedScript { span }
+            | ResourceNameError::InvalidPercentEncoding { span }
+            | ResourceNameError::InvalidUrn { span }
+            | ResourceNameError::UnterminatedVariable { span }
+            | ResourceNameError::InvalidVariable { span }
+            | ResourceNameError::UnresolvedVariable { span }
+            | ResourceNameError::InvalidVariableValue { span } => *span,
+        }
+    }
+
+    /// the same error, with its span replaced by `f(span)`. `Empty` has no
+    /// span to replace.
+    pub(crate) fn map_span(mut self, f: impl FnOnce(Span) -> Span) -> Self {
+        match &mut self {
+            ResourceNameError::Empty => {}
+            ResourceNameError::InvalidSegmentCount { span, .. }
+            | ResourceNameError::EmptyPrefix { span }
+            | ResourceNameError::EmptyPartition { span }
+            | ResourceNameError::EmptyService { span }
+            | ResourceNameError::EmptyQualifiedResourcePath { span }
+            | ResourceNameError::EmptyResourceType { span }
+            | ResourceNameError::EmptyResourcePath { span }
+            | ResourceNameError::InvalidResourcePathSegment { span }
+            | ResourceNameError::EmptySegment { span, .. }
+            | ResourceNameError::InvalidLeader { span, .. }
+            | ResourceNameError::InvalidArnPrefix { span }
+            | ResourceNameError::InvalidArnPartition { span }
+            | ResourceNameError::InvalidCharacter { span }
+            | ResourceNameError::NonCanonical { span }
+            | ResourceNameError::MixedScript { span }
+            | ResourceNameError::InvalidPercentEncoding { span }
+            | ResourceNameError::InvalidUrn { span }
+            | ResourceNameError::UnterminatedVariable { span }
+            | ResourceNameError::InvalidVariable { span }
+            | ResourceNameError::UnresolvedVariable { span }
+            | ResourceNameError::InvalidVariableValue { span } => *span = f(*span),
+        }
+        self
+    }
+
+    /// the name of the variant, e.g. `"EmptyService"`, for grouping errors
+    /// (see `ErrorStats`) without caring about where they occurred.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ResourceNameError::Empty => "Empty",
+            ResourceNameError::InvalidSegmentCount { .. } => "InvalidSegmentCount",
+            ResourceNameError::EmptyPrefix { .. } => "EmptyPrefix",
+            ResourceNameError::EmptyPartition { .. } => "EmptyPartition",
+            ResourceNameError::EmptyService { .. } => "EmptyService",
+            ResourceNameError::EmptyQualifiedResourcePath { .. } => "EmptyQualifiedResourcePath",
+            ResourceNameError::EmptyResourceType { .. } => "EmptyResourceType",
+            ResourceNameError::EmptyResourcePath { .. } => "EmptyResourcePath",
+            ResourceNameError::InvalidResourcePathSegment { .. } => "InvalidResourcePathSegment",
+            ResourceNameError::EmptySegment { .. } => "EmptySegment",
+            ResourceNameError::InvalidLeader { .. } => "InvalidLeader",
+            ResourceNameError::InvalidArnPrefix { .. } => "InvalidArnPrefix",
+            ResourceNameError::InvalidArnPartition { .. } => "InvalidArnPartition",
+            ResourceNameError::InvalidCharacter { .. } => "InvalidCharacter",
+            ResourceNameError::NonCanonical { .. } => "NonCanonical",
+            ResourceNameError::MixedScript { .. } => "MixedScript",
+            ResourceNameError::InvalidPercentEncoding { .. } => "InvalidPercentEncoding",
+            ResourceNameError::InvalidUrn { .. } => "InvalidUrn",
+            ResourceNameError::UnterminatedVariable { .. } => "UnterminatedVariable",
+            ResourceNameError::InvalidVariable { .. } => "InvalidVariable",
+            ResourceNameError::UnresolvedVariable { .. } => "UnresolvedVariable",
+            ResourceNameError::InvalidVariableValue { .. } => "InvalidVariableValue",
+        }
+    }
+
+    /// render the error against the input which caused it, underlining the
+    /// offending bytes. `input` must be the exact string that was parsed.
+    pub fn render<'a>(&'a self, input: &'a str) -> Diagnostic<'a> {
+        Diagnostic::new(self, input)
+    }
 }
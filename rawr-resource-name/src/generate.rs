@@ -0,0 +1,279 @@
+//! grammar-aware generation of resource names, shared by the `arbitrary` and
+//! `proptest` features.
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::RangeInclusive;
+
+use crate::resource_name::{RESOURCE_PATH_SEGMENT_SEPARATOR, SEGMENT_SEPARATOR};
+use crate::{ResourceName, ResourceNameBuf};
+
+/// the characters a generated {prefix} is made of.
+pub(crate) const PREFIX_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz";
+
+/// the characters the other generated segments are made of.
+pub(crate) const SEGMENT_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789-";
+
+/// the characters each '/' separated {resource_path} segment is made of.
+pub(crate) const PATH_ALPHABET: &[u8] = b"abcdefghijklmnopqrstuvwxyz0123456789-_.";
+
+/// the length of a generated {prefix}.
+pub(crate) const PREFIX_LEN: RangeInclusive<usize> = 3..=3;
+
+/// the length of every other generated segment.
+pub(crate) const SEGMENT_LEN: RangeInclusive<usize> = 1..=16;
+
+/// how many '/' separated segments a generated {resource_path} has.
+pub(crate) const PATH_SEGMENTS: RangeInclusive<usize> = 1..=4;
+
+/// the segments of a generated resource name. {region} and {account_id} are
+/// empty for global resources.
+pub(crate) struct Generated {
+    pub(crate) prefix: String,
+    pub(crate) partition: String,
+    pub(crate) service: String,
+    pub(crate) region: String,
+    pub(crate) account_id: String,
+    pub(crate) resource_type: String,
+    pub(crate) resource_path: Vec<String>,
+}
+
+impl Generated {
+    /// assemble the segments into a resource name.
+    pub(crate) fn into_buf(self) -> ResourceNameBuf {
+        let name = [
+            self.prefix,
+            self.partition,
+            self.service,
+            self.region,
+            self.account_id,
+            self.resource_type,
+        ]
+        .join(SEGMENT_SEPARATOR);
+        let name = format!(
+            "{name}{RESOURCE_PATH_SEGMENT_SEPARATOR}{}",
+            self.resource_path.join(RESOURCE_PATH_SEGMENT_SEPARATOR)
+        );
+        ResourceNameBuf::parse(&name).expect("generated resource names are valid")
+    }
+}
+
+/// a way of breaking a valid resource name, one for each error
+/// `ResourceName::parse` can report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Mutation {
+    /// nothing at all, `ResourceNameError::Empty`.
+    Empty,
+    /// a missing ':', `ResourceNameError::InvalidSegmentCount`.
+    TooFewSegments,
+    /// a trailing ':extra', `ResourceNameError::InvalidSegmentCount`.
+    TooManySegments,
+    /// `ResourceNameError::EmptyPrefix`.
+    EmptyPrefix,
+    /// `ResourceNameError::EmptyPartition`.
+    EmptyPartition,
+    /// `ResourceNameError::EmptyService`.
+    EmptyService,
+    /// nothing after the {account_id},
+    /// `ResourceNameError::EmptyQualifiedResourcePath`.
+    EmptyQualifiedResourcePath,
+    /// `ResourceNameError::EmptyResourceType`.
+    EmptyResourceType,
+    /// `ResourceNameError::EmptyResourcePath`.
+    EmptyResourcePath,
+    /// a trailing DEL, `ResourceNameError::InvalidCharacter`.
+    ControlCharacter,
+}
+
+impl Mutation {
+    /// every mutation.
+    pub const ALL: [Mutation; 10] = [
+        Mutation::Empty,
+        Mutation::TooFewSegments,
+        Mutation::TooManySegments,
+        Mutation::EmptyPrefix,
+        Mutation::EmptyPartition,
+        Mutation::EmptyService,
+        Mutation::EmptyQualifiedResourcePath,
+        Mutation::EmptyResourceType,
+        Mutation::EmptyResourcePath,
+        Mutation::ControlCharacter,
+    ];
+
+    /// break `rn`, which must be a rawr-style name (i.e. '/' after the
+    /// {resource_type}, as produced by `ResourceName::parse`).
+    pub fn apply(self, rn: &ResourceName<'_>) -> String {
+        match self {
+            Mutation::Empty => String::new(),
+            Mutation::TooFewSegments => rn.to_string().replacen(SEGMENT_SEPARATOR, "", 1),
+            Mutation::TooManySegments => format!("{rn}{SEGMENT_SEPARATOR}extra"),
+            Mutation::EmptyPrefix => ResourceName { prefix: "", ..*rn }.to_string(),
+            Mutation::EmptyPartition => ResourceName {
+                partition: "",
+                ..*rn
+            }
+            .to_string(),
+            Mutation::EmptyService => ResourceName { service: "", ..*rn }.to_string(),
+            Mutation::EmptyQualifiedResourcePath => ResourceName {
+                resource_type: "",
+                resource_separator: "",
+                resource_path: "",
+                ..*rn
+            }
+            .to_string(),
+            Mutation::EmptyResourceType => ResourceName {
+                resource_type: "",
+                ..*rn
+            }
+            .to_string(),
+            Mutation::EmptyResourcePath => ResourceName {
+                resource_path: "",
+                ..*rn
+            }
+            .to_string(),
+            Mutation::ControlCharacter => format!("{rn}\u{7f}"),
+        }
+    }
+
+    /// the `ResourceNameError::kind` that parsing the mutated name fails
+    /// with.
+    pub fn expected_kind(self) -> &'static str {
+        match self {
+            Mutation::Empty => "Empty",
+            Mutation::TooFewSegments | Mutation::TooManySegments => "InvalidSegmentCount",
+            Mutation::EmptyPrefix => "EmptyPrefix",
+            Mutation::EmptyPartition => "EmptyPartition",
+            Mutation::EmptyService => "EmptyService",
+            Mutation::EmptyQualifiedResourcePath => "EmptyQualifiedResourcePath",
+            Mutation::EmptyResourceType => "EmptyResourceType",
+            Mutation::EmptyResourcePath => "EmptyResourcePath",
+            Mutation::ControlCharacter => "InvalidCharacter",
+        }
+    }
+}
+
+/// a near-valid resource name: a valid one, broken by `mutation`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MutatedResourceName {
+    /// the broken resource name.
+    pub input: String,
+    /// how it was broken.
+    pub mutation: Mutation,
+}
+
+impl MutatedResourceName {
+    /// break `rn` with `mutation`.
+    pub fn new(rn: &ResourceName<'_>, mutation: Mutation) -> Self {
+        MutatedResourceName {
+            input: mutation.apply(rn),
+            mutation,
+        }
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+mod fuzz {
+    use alloc::string::String;
+    use core::ops::RangeInclusive;
+
+    use arbitrary::{Arbitrary, Result, Unstructured};
+
+    use super::*;
+
+    /// a segment of `len` characters from `alphabet`.
+    fn segment(
+        u: &mut Unstructured<'_>,
+        alphabet: &[u8],
+        len: RangeInclusive<usize>,
+    ) -> Result<String> {
+        let len = u.int_in_range(len)?;
+        (0..len)
+            .map(|_| u.choose(alphabet).map(|byte| char::from(*byte)))
+            .collect()
+    }
+
+    /// a segment which is empty for global resources.
+    fn optional_segment(u: &mut Unstructured<'_>) -> Result<String> {
+        if u.arbitrary()? {
+            segment(u, SEGMENT_ALPHABET, SEGMENT_LEN)
+        } else {
+            Ok(String::new())
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for ResourceNameBuf {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let path_segments = u.int_in_range(PATH_SEGMENTS)?;
+            Ok(Generated {
+                prefix: segment(u, PREFIX_ALPHABET, PREFIX_LEN)?,
+                partition: segment(u, SEGMENT_ALPHABET, SEGMENT_LEN)?,
+                service: segment(u, SEGMENT_ALPHABET, SEGMENT_LEN)?,
+                region: optional_segment(u)?,
+                account_id: optional_segment(u)?,
+                resource_type: segment(u, SEGMENT_ALPHABET, SEGMENT_LEN)?,
+                resource_path: (0..path_segments)
+                    .map(|_| segment(u, PATH_ALPHABET, SEGMENT_LEN))
+                    .collect::<Result<_>>()?,
+            }
+            .into_buf())
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for Mutation {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            u.choose(&Mutation::ALL).copied()
+        }
+    }
+
+    impl<'a> Arbitrary<'a> for MutatedResourceName {
+        fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+            let rn = ResourceNameBuf::arbitrary(u)?;
+            Ok(MutatedResourceName::new(
+                &rn.as_resource_name(),
+                Mutation::arbitrary(u)?,
+            ))
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use alloc::vec::Vec;
+
+        use super::*;
+        use crate::ResourceName;
+
+        /// deterministic "random" bytes, enough for a handful of names.
+        fn noise(seed: u64) -> Vec<u8> {
+            let mut state = seed;
+            (0..512)
+                .map(|_| {
+                    state = state
+                        .wrapping_mul(6_364_136_223_846_793_005)
+                        .wrapping_add(1_442_695_040_888_963_407);
+                    (state >> 56) as u8
+                })
+                .collect()
+        }
+
+        #[test]
+        fn arbitrary_names_round_trip() {
+            for seed in 0..256 {
+                let data = noise(seed);
+                let mut u = Unstructured::new(&data);
+                let rn = ResourceNameBuf::arbitrary(&mut u).unwrap();
+                assert_eq!(ResourceName::parse(rn.as_str()).unwrap(), rn);
+            }
+        }
+
+        #[test]
+        fn arbitrary_mutations_fail_as_expected() {
+            for seed in 0..256 {
+                let data = noise(seed);
+                let mut u = Unstructured::new(&data);
+                let mutated = MutatedResourceName::arbitrary(&mut u).unwrap();
+                let err = ResourceName::parse(&mutated.input).unwrap_err();
+                assert_eq!(err.kind(), mutated.mutation.expected_kind(), "{mutated:?}");
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use alloc::format;
+use core::iter::FusedIterator;
+
+use crate::resource_name::{RESOURCE_PATH_SEGMENT_SEPARATOR, SEGMENT_SEPARATOR};
+use crate::{ResourceName, ResourceNameBuf, ResourceNameError, Span};
+
+/// hierarchy operations over the {resource_path}.
+///
+/// a resource path such as `157807aa/zone/mhq-eu-primary` is treated as a
+/// chain of nested resources, scoped by everything to the left of it. two
+/// names are only ever related when their {prefix}, {partition}, {service},
+/// {region}, {account_id} and {resource_type} are identical - a `zone` under
+/// one account is never the child of a `control-plane` under another.
+impl<'a> ResourceName<'a> {
+    /// the parent of this resource, or `None` if the resource path has a
+    /// single segment (i.e. it's a top-level resource of its type) or the
+    /// parent's path would be empty (e.g. `member//x`).
+    ///
+    /// `mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres/bay-12` ->
+    /// `mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres`
+    pub fn parent(&self) -> Option<ResourceName<'a>> {
+        let (parent_path, _) = self
+            .resource_path
+            .rsplit_once(RESOURCE_PATH_SEGMENT_SEPARATOR)?;
+        if parent_path.is_empty() {
+            return None;
+        }
+
+        Some(ResourceName {
+            resource_path: parent_path,
+            ..*self
+        })
+    }
+
+    /// iterate over this resource and each of its parents, nearest first.
+    pub fn ancestors(&self) -> Ancestors<'a> {
+        Ancestors { next: Some(*self) }
+    }
+
+    /// create a child of this resource by appending a single segment to the
+    /// resource path.
+    ///
+    /// the segment must be non-empty and may not contain either separator,
+    /// error spans are relative to `segment`. a resource without a
+    /// {resource_type} (a bare ARN resource, e.g. `arn:aws:s3:::bucket`) has
+    /// no children, joining it is an `EmptyResourceType` error since the
+    /// child would re-parse with the parent's path as its type.
+    pub fn join(&self, segment: &str) -> Result<ResourceNameBuf, ResourceNameError> {
+        if self.resource_separator.is_empty() {
+            return Err(ResourceNameError::EmptyResourceType { span: Span::at(0) });
+        }
+        if segment.is_empty() {
+            return Err(ResourceNameError::InvalidResourcePathSegment { span: Span::at(0) });
+        }
+        if let Some(offset) = [SEGMENT_SEPARATOR, RESOURCE_PATH_SEGMENT_SEPARATOR]
+            .iter()
+            .filter_map(|separator| segment.find(separator))
+            .min()
+        {
+            return Err(ResourceNameError::InvalidResourcePathSegment {
+                span: Span::new(offset, offset + 1),
+            });
+        }
+
+        let resource_path = format!(
+            "{path}{RESOURCE_PATH_SEGMENT_SEPARATOR}{segment}",
+            path = self.resource_path
+        );
+
+        Ok(ResourceNameBuf::from(&ResourceName {
+            resource_path: &resource_path,
+            ..*self
+        }))
+    }
+
+    /// whether `other` is nested somewhere beneath this resource. a resource
+    /// is never its own ancestor.
+    pub fn is_ancestor_of(&self, other: &ResourceName<'_>) -> bool {
+        self.same_scope(other)
+            && other
+                .resource_path
+                .strip_prefix(self.resource_path)
+                .is_some_and(|rest| rest.starts_with(RESOURCE_PATH_SEGMENT_SEPARATOR))
+    }
+
+    /// whether this resource is `base` or nested somewhere beneath it, the
+    /// "this resource and everything under it" check.
+    pub fn starts_with(&self, base: &ResourceName<'_>) -> bool {
+        (self.same_scope(base) && self.resource_path == base.resource_path)
+            || base.is_ancestor_of(self)
+    }
+
+    fn same_scope(&self, other: &ResourceName<'_>) -> bool {
+        self.prefix == other.prefix
+            && self.partition == other.partition
+            && self.service == other.service
+            && self.region == other.region
+            && self.account_id == other.account_id
+            && self.resource_type == other.resource_type
+            && self.resource_separator == other.resource_separator
+    }
+}
+
+/// an iterator over a `ResourceName` and its parents, see
+/// `ResourceName::ancestors`.
+#[derive(Debug, Clone)]
+pub struct Ancestors<'a> {
+    next: Option<ResourceName<'a>>,
+}
+
+impl<'a> Iterator for Ancestors<'a> {
+    type Item = ResourceName<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next.take()?;
+        self.next = current.parent();
+        Some(current)
+    }
+}
+
+impl FusedIterator for Ancestors<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    const VALID_RESOURCE_NAME_KONNECT: &str = "krn:konnect:mesh:eu:df40c456-7dbb-4fbf-8b2c-a1c89997b7c4:control-plane/157807aa-3a85-4504-8340-ad9c0baae569/zone/mhq-eu-primary";
+
+    #[test]
+    fn parent_strips_last_path_segment() {
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME_KONNECT).unwrap();
+        let parent = rn.parent().unwrap();
+        assert_eq!(
+            parent.to_string(),
+            "krn:konnect:mesh:eu:df40c456-7dbb-4fbf-8b2c-a1c89997b7c4:control-plane/157807aa-3a85-4504-8340-ad9c0baae569/zone"
+        );
+    }
+
+    #[test]
+    fn parent_of_top_level_resource() {
+        let rn = ResourceName::parse("mrn:tycho:opa:::member/anderson-dawes").unwrap();
+        assert!(rn.parent().is_none());
+    }
+
+    #[test]
+    fn parent_path_is_never_empty() {
+        let rn = ResourceName::parse("mrn:a:b:c:d:member//x").unwrap();
+        assert!(rn.parent().is_none());
+
+        let rn = ResourceName::parse("mrn:a:b:c:d:member/x//y").unwrap();
+        let paths: Vec<&str> = rn.ancestors().map(|a| a.resource_path()).collect();
+        assert_eq!(paths, vec!["x//y", "x/", "x"]);
+        for ancestor in rn.ancestors() {
+            assert_eq!(
+                ResourceName::parse(&ancestor.to_string()).unwrap(),
+                ancestor
+            );
+        }
+    }
+
+    #[test]
+    fn ancestors_walk_to_the_top() {
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME_KONNECT).unwrap();
+        let paths: Vec<&str> = rn.ancestors().map(|a| a.resource_path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "157807aa-3a85-4504-8340-ad9c0baae569/zone/mhq-eu-primary",
+                "157807aa-3a85-4504-8340-ad9c0baae569/zone",
+                "157807aa-3a85-4504-8340-ad9c0baae569",
+            ]
+        );
+    }
+
+    #[test]
+    fn join_appends_a_segment() {
+        let rn = ResourceName::parse("mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres").unwrap();
+        let child = rn.join("bay-12").unwrap();
+        assert_eq!(
+            child.as_str(),
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres/bay-12"
+        );
+        assert!(rn.is_ancestor_of(&child.as_resource_name()));
+    }
+
+    #[test]
+    fn join_round_trips_arns() {
+        for s in [
+            "arn:aws:iam::123456789012:role/path",
+            "arn:aws:lambda:us-east-1:123456789012:function:my-fn",
+        ] {
+            let child = ResourceName::parse_arn(s).unwrap().join("x").unwrap();
+            assert_eq!(
+                ResourceName::parse_arn(child.as_str()).unwrap(),
+                child.as_resource_name()
+            );
+        }
+
+        let bucket = ResourceName::parse_arn("arn:aws:s3:::bucket").unwrap();
+        assert_eq!(
+            bucket.join("key").unwrap_err(),
+            ResourceNameError::EmptyResourceType { span: Span::at(0) }
+        );
+    }
+
+    #[test]
+    fn join_rejects_invalid_segments() {
+        let rn = ResourceName::parse("mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres").unwrap();
+        for (segment, span) in [
+            ("", Span::at(0)),
+            ("bay/12", Span::new(3, 4)),
+            ("bay:1/2", Span::new(3, 4)),
+        ] {
+            assert_eq!(
+                rn.join(segment).unwrap_err(),
+                ResourceNameError::InvalidResourcePathSegment { span }
+            );
+        }
+    }
+
+    #[test]
+    fn is_ancestor_of_respects_segment_boundaries() {
+        let base = ResourceName::parse("mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres").unwrap();
+        let child =
+            ResourceName::parse("mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres/bay-12").unwrap();
+        let sibling =
+            ResourceName::parse("mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres-2").unwrap();
+
+        assert!(base.is_ancestor_of(&child));
+        assert!(!child.is_ancestor_of(&base));
+        assert!(!base.is_ancestor_of(&base));
+        assert!(!base.is_ancestor_of(&sibling));
+    }
+
+    #[test]
+    fn is_ancestor_of_respects_scope() {
+        let base = ResourceName::parse("mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres").unwrap();
+        for other in [
+            "mrn:tycho:opa:sol-belt-1:otheraccount:station/ceres/bay-12",
+            "mrn:tycho:opa:sol-belt-2:36UeVtK7:station/ceres/bay-12",
+            "mrn:tycho:mcrn:sol-belt-1:36UeVtK7:station/ceres/bay-12",
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7:ship/ceres/bay-12",
+        ] {
+            let other = ResourceName::parse(other).unwrap();
+            assert!(!base.is_ancestor_of(&other));
+            assert!(!other.starts_with(&base));
+        }
+    }
+
+    #[test]
+    fn starts_with_includes_self() {
+        let base = ResourceName::parse("mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres").unwrap();
+        let child =
+            ResourceName::parse("mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres/bay-12").unwrap();
+
+        assert!(base.starts_with(&base));
+        assert!(child.starts_with(&base));
+        assert!(!base.starts_with(&child));
+    }
+}
@@ -0,0 +1,451 @@
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use crate::resource_name::{RESOURCE_PATH_SEGMENT_SEPARATOR, SEGMENT_SEPARATOR};
+use crate::{ResourceName, ResourceNameError};
+
+/// every separator which may sit between {resource_type} and {resource_path},
+/// interned names store an index into this.
+const RESOURCE_SEPARATORS: [&str; 3] = [RESOURCE_PATH_SEGMENT_SEPARATOR, SEGMENT_SEPARATOR, ""];
+
+/// marks an unused slot in a lookup table.
+const EMPTY_SLOT: u32 = u32::MAX;
+
+/// a lookup table is grown once it's more than half full.
+const MAX_LOAD_FACTOR: usize = 2;
+
+/// the number of slots a lookup table starts with.
+const INITIAL_SLOTS: usize = 64;
+
+/// FNV-1a 64-bit offset basis.
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// FNV-1a 64-bit prime.
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// terminates each segment of a name as it is hashed, a byte which never
+/// appears in utf-8.
+const SEGMENT_TERMINATOR: u8 = 0xFF;
+
+/// a handle to a resource name held by a `ResourceNameInterner`.
+///
+/// handles are only meaningful to the interner which created them. two
+/// handles from the same interner are equal if and only if they refer to the
+/// same resource name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct InternedResourceName(u32);
+
+/// the {prefix}, {partition}, {service}, {region} and {account_id} symbols
+/// shared by every name in the same scope.
+type Scope = [u32; 5];
+
+/// an interned resource name, 20 bytes plus its {resource_path}.
+#[derive(Debug, Clone, Copy)]
+struct Entry {
+    scope: u32,
+    resource_type: u32,
+    resource_separator: u8,
+    path_start: u32,
+    path_len: u32,
+}
+
+/// a deduplicating store for large numbers of resource names.
+///
+/// most names share their leading segments, so rather than storing each name
+/// in full:
+/// - {prefix}, {partition}, {service}, {region}, {account_id} and
+///   {resource_type} values are interned once, as shared symbols.
+/// - each distinct combination of the five leading segments (a "scope") is
+///   stored once.
+/// - {resource_path}s are appended to a single arena.
+///
+/// interning the same name twice hands back the same `InternedResourceName`,
+/// which resolves back into a borrowed `ResourceName<'_>`.
+///
+/// an interner holds at most `u32::MAX - 1` names, symbols and scopes, and at
+/// most 4GiB of resource paths. interning beyond that panics.
+#[derive(Debug, Clone)]
+pub struct ResourceNameInterner {
+    symbols: Vec<Box<str>>,
+    // open addressing over `symbols`, so each symbol is stored once.
+    symbol_slots: Vec<u32>,
+    scopes: Vec<Scope>,
+    scope_ids: BTreeMap<Scope, u32>,
+    entries: Vec<Entry>,
+    paths: String,
+    // open addressing over `entries`, so names are deduplicated without a
+    // second copy of every path.
+    slots: Vec<u32>,
+}
+
+impl Default for ResourceNameInterner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ResourceNameInterner {
+    /// create an empty interner.
+    pub fn new() -> Self {
+        ResourceNameInterner {
+            symbols: Vec::new(),
+            symbol_slots: vec![EMPTY_SLOT; INITIAL_SLOTS],
+            scopes: Vec::new(),
+            scope_ids: BTreeMap::new(),
+            entries: Vec::new(),
+            paths: String::new(),
+            slots: vec![EMPTY_SLOT; INITIAL_SLOTS],
+        }
+    }
+
+    /// the number of distinct resource names interned.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// whether no resource names have been interned.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// the number of distinct segment values (and resource types) shared
+    /// between the interned names.
+    pub fn symbol_count(&self) -> usize {
+        self.symbols.len()
+    }
+
+    /// the number of distinct {prefix}..{account_id} combinations shared
+    /// between the interned names.
+    pub fn scope_count(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// intern `rn`, returning the existing handle if it's already interned.
+    pub fn intern(&mut self, rn: &ResourceName<'_>) -> InternedResourceName {
+        if let Some(handle) = self.get(rn) {
+            return handle;
+        }
+
+        let scope = [
+            self.intern_symbol(rn.prefix),
+            self.intern_symbol(rn.partition),
+            self.intern_symbol(rn.service),
+            self.intern_symbol(rn.region),
+            self.intern_symbol(rn.account_id),
+        ];
+        let scope = match self.scope_ids.get(&scope) {
+            Some(id) => *id,
+            None => {
+                let id = to_u32(self.scopes.len());
+                self.scopes.push(scope);
+                self.scope_ids.insert(scope, id);
+                id
+            }
+        };
+
+        let entry = Entry {
+            scope,
+            resource_type: self.intern_symbol(rn.resource_type),
+            resource_separator: separator_index(rn.resource_separator),
+            path_start: to_u32(self.paths.len()),
+            path_len: to_u32(rn.resource_path.len()),
+        };
+        self.paths.push_str(rn.resource_path);
+        // the end of the path must be addressable too
+        to_u32(self.paths.len());
+
+        let handle = to_u32(self.entries.len());
+        self.entries.push(entry);
+        if self.entries.len() * MAX_LOAD_FACTOR > self.slots.len() {
+            self.grow();
+        } else {
+            let slot = vacant_slot(&self.slots, self.hash(&self.resolve_entry(entry)));
+            self.slots[slot] = handle;
+        }
+
+        InternedResourceName(handle)
+    }
+
+    /// parse and intern `input`.
+    pub fn intern_str(&mut self, input: &str) -> Result<InternedResourceName, ResourceNameError> {
+        let rn = ResourceName::parse(input)?;
+        Ok(self.intern(&rn))
+    }
+
+    /// the handle for `rn`, if it has been interned.
+    pub fn get(&self, rn: &ResourceName<'_>) -> Option<InternedResourceName> {
+        let mask = self.slots.len() - 1;
+        let mut slot = self.hash(rn) as usize & mask;
+        loop {
+            match self.slots[slot] {
+                EMPTY_SLOT => return None,
+                handle if self.resolve_entry(self.entries[handle as usize]) == *rn => {
+                    return Some(InternedResourceName(handle));
+                }
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+    }
+
+    /// resolve a handle back into a `ResourceName`.
+    ///
+    /// panics if `handle` was created by a different interner (and is out of
+    /// range for this one).
+    pub fn resolve(&self, handle: InternedResourceName) -> ResourceName<'_> {
+        self.resolve_entry(self.entries[handle.0 as usize])
+    }
+
+    /// every interned resource name, in the order they were first interned.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (InternedResourceName, ResourceName<'_>)> {
+        self.entries
+            .iter()
+            .enumerate()
+            .map(|(idx, entry)| (InternedResourceName(idx as u32), self.resolve_entry(*entry)))
+    }
+
+    fn intern_symbol(&mut self, value: &str) -> u32 {
+        let mask = self.symbol_slots.len() - 1;
+        let mut slot = fnv1a(FNV_OFFSET_BASIS, value.as_bytes()) as usize & mask;
+        loop {
+            match self.symbol_slots[slot] {
+                EMPTY_SLOT => break,
+                id if *self.symbols[id as usize] == *value => return id,
+                _ => slot = (slot + 1) & mask,
+            }
+        }
+
+        let id = to_u32(self.symbols.len());
+        self.symbols.push(value.into());
+        if self.symbols.len() * MAX_LOAD_FACTOR > self.symbol_slots.len() {
+            self.grow_symbols();
+        } else {
+            self.symbol_slots[slot] = id;
+        }
+        id
+    }
+
+    fn resolve_entry(&self, entry: Entry) -> ResourceName<'_> {
+        let [prefix, partition, service, region, account_id] = self.scopes[entry.scope as usize];
+        let path_start = entry.path_start as usize;
+        ResourceName {
+            prefix: &self.symbols[prefix as usize],
+            partition: &self.symbols[partition as usize],
+            service: &self.symbols[service as usize],
+            region: &self.symbols[region as usize],
+            account_id: &self.symbols[account_id as usize],
+            resource_type: &self.symbols[entry.resource_type as usize],
+            resource_separator: RESOURCE_SEPARATORS[usize::from(entry.resource_separator)],
+            resource_path: &self.paths[path_start..path_start + entry.path_len as usize],
+        }
+    }
+
+    /// hash a name by value, so interned and not-yet-interned names agree.
+    fn hash(&self, rn: &ResourceName<'_>) -> u64 {
+        [
+            rn.prefix,
+            rn.partition,
+            rn.service,
+            rn.region,
+            rn.account_id,
+            rn.resource_type,
+            rn.resource_separator,
+            rn.resource_path,
+        ]
+        .iter()
+        .fold(FNV_OFFSET_BASIS, |hash, segment| {
+            // terminate each segment, so ("ab", "c") and ("a", "bc") differ
+            fnv1a(fnv1a(hash, segment.as_bytes()), &[SEGMENT_TERMINATOR])
+        })
+    }
+
+    /// double the lookup table, and re-insert every name.
+    fn grow(&mut self) {
+        self.slots = vec![EMPTY_SLOT; self.slots.len() * 2];
+        for handle in 0..self.entries.len() {
+            let hash = self.hash(&self.resolve_entry(self.entries[handle]));
+            let slot = vacant_slot(&self.slots, hash);
+            self.slots[slot] = handle as u32;
+        }
+    }
+
+    /// double the symbol lookup table, and re-insert every symbol.
+    fn grow_symbols(&mut self) {
+        self.symbol_slots = vec![EMPTY_SLOT; self.symbol_slots.len() * 2];
+        for (id, symbol) in self.symbols.iter().enumerate() {
+            let slot = vacant_slot(
+                &self.symbol_slots,
+                fnv1a(FNV_OFFSET_BASIS, symbol.as_bytes()),
+            );
+            self.symbol_slots[slot] = id as u32;
+        }
+    }
+}
+
+/// the first unused slot of `slots` at or after `hash`.
+fn vacant_slot(slots: &[u32], hash: u64) -> usize {
+    let mask = slots.len() - 1;
+    let mut slot = hash as usize & mask;
+    while slots[slot] != EMPTY_SLOT {
+        slot = (slot + 1) & mask;
+    }
+    slot
+}
+
+/// FNV-1a of `bytes`, continuing from `hash` (`FNV_OFFSET_BASIS` to start
+/// afresh).
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+/// the index of `separator` within `RESOURCE_SEPARATORS`.
+fn separator_index(separator: &str) -> u8 {
+    RESOURCE_SEPARATORS
+        .iter()
+        .position(|s| *s == separator)
+        .expect("resource separators are always one of RESOURCE_SEPARATORS") as u8
+}
+
+fn to_u32(value: usize) -> u32 {
+    u32::try_from(value)
+        .ok()
+        .filter(|value| *value != EMPTY_SLOT)
+        .expect("resource name interner is full")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_RESOURCE_NAME: &str =
+        "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes";
+
+    #[test]
+    fn fnv1a_matches_reference_vectors() {
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a(FNV_OFFSET_BASIS, b"foobar"), 0x8594_4171_f739_67e8);
+        assert_eq!(
+            fnv1a(fnv1a(FNV_OFFSET_BASIS, b"foo"), b"bar"),
+            fnv1a(FNV_OFFSET_BASIS, b"foobar")
+        );
+    }
+
+    #[test]
+    fn intern_and_resolve() {
+        let mut interner = ResourceNameInterner::new();
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME).unwrap();
+
+        let handle = interner.intern(&rn);
+        assert_eq!(interner.resolve(handle), rn);
+        assert_eq!(interner.get(&rn), Some(handle));
+        assert_eq!(interner.len(), 1);
+    }
+
+    #[test]
+    fn duplicates_share_a_handle() {
+        let mut interner = ResourceNameInterner::new();
+        let a = interner.intern_str(VALID_RESOURCE_NAME).unwrap();
+        let b = interner.intern_str(VALID_RESOURCE_NAME).unwrap();
+        let c = interner
+            .intern_str("mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/naomi-nagata")
+            .unwrap();
+
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+        assert_eq!(interner.len(), 2);
+    }
+
+    #[test]
+    fn leading_segments_are_shared() {
+        let mut interner = ResourceNameInterner::new();
+        for (account, path) in [
+            ("36UeVtK7", "anderson-dawes"),
+            ("36UeVtK7", "naomi-nagata"),
+            ("4Kb9yq2B", "anderson-dawes"),
+        ] {
+            interner
+                .intern_str(&alloc::format!(
+                    "mrn:tycho:opa:sol-belt-1:{account}:member/{path}"
+                ))
+                .unwrap();
+        }
+
+        assert_eq!(interner.len(), 3);
+        assert_eq!(interner.scope_count(), 2);
+        // mrn, tycho, opa, sol-belt-1, two accounts and member
+        assert_eq!(interner.symbol_count(), 7);
+    }
+
+    #[test]
+    fn many_names_survive_growth() {
+        let mut interner = ResourceNameInterner::new();
+        let names: Vec<String> = (0..1000)
+            .map(|i| alloc::format!("mrn:tycho:opa:::station/ceres/bay-{i}"))
+            .collect();
+
+        let handles: Vec<_> = names
+            .iter()
+            .map(|name| interner.intern_str(name).unwrap())
+            .collect();
+
+        assert_eq!(interner.len(), names.len());
+        for (name, handle) in names.iter().zip(&handles) {
+            assert_eq!(interner.resolve(*handle).to_buf().as_str(), name);
+            assert_eq!(
+                interner.get(&ResourceName::parse(name).unwrap()),
+                Some(*handle)
+            );
+        }
+        assert_eq!(interner.iter().count(), names.len());
+    }
+
+    #[test]
+    fn many_symbols_survive_growth() {
+        let mut interner = ResourceNameInterner::new();
+        let names: Vec<String> = (0..200)
+            .map(|i| alloc::format!("mrn:tycho:opa:region-{i}:account-{i}:type-{i}/x"))
+            .collect();
+
+        let handles: Vec<_> = names
+            .iter()
+            .map(|name| interner.intern_str(name).unwrap())
+            .collect();
+        for name in &names {
+            interner.intern_str(name).unwrap();
+        }
+
+        // mrn, tycho, opa, then a region, account and type per name
+        assert_eq!(interner.symbol_count(), 3 + 3 * names.len());
+        for (name, handle) in names.iter().zip(&handles) {
+            assert_eq!(interner.resolve(*handle).to_buf().as_str(), name);
+        }
+    }
+
+    #[test]
+    fn arn_separators_are_preserved() {
+        let mut interner = ResourceNameInterner::new();
+        for input in [
+            "arn:aws:lambda:us-east-1:123456789012:function:my-fn",
+            "arn:aws:s3:::my-bucket",
+            "arn:aws:ec2:us-east-1:123456789012:instance/i-0123",
+        ] {
+            let rn = ResourceName::parse_arn(input).unwrap();
+            let handle = interner.intern(&rn);
+            assert_eq!(interner.resolve(handle), rn);
+        }
+        assert_eq!(interner.len(), 3);
+    }
+
+    #[test]
+    fn get_does_not_intern() {
+        let interner = ResourceNameInterner::new();
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME).unwrap();
+        assert_eq!(interner.get(&rn), None);
+        assert!(interner.is_empty());
+    }
+}
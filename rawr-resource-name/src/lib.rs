@@ -17,8 +17,95 @@
 //! - the {resource_path} must be a valid unicode string.
 //! - the combination of {resource_type} and {resource_path} are known as the
 //!   "qualified resource path".
+//! - the {resource_path} is hierarchical, each '/' separated segment nests
+//!   beneath the one before it (see `ResourceName::parent`).
+//! - no segment may contain an ascii control character.
+//!
+//! the format above is the default `ResourceNameSchema`, identifiers with a
+//! different shape can describe their own schema and parse with it.
+//!
+//! AWS ARNs, whose resource may also be `{resource_type}:{resource_path}` or a
+//! bare `{resource_path}`, can be parsed with `ResourceName::parse_arn`. the
+//! owned, serde and URI/URN parsers accept either form, so names from both
+//! parsers round trip.
+//!
+//! names compared across a trust boundary should be canonicalized first (see
+//! `ResourceName::canonicalize` and `ResourceName::parse_strict`), otherwise
+//! visually identical names (e.g. NFC vs NFD) are different resources.
+//!
+//! `:` and `/` collide with URI syntax, `ResourceName::to_uri_component` and
+//! `ResourceName::to_urn` (and their `from_` counterparts) escape them.
+//!
+//! policies which refer to "the caller's own resources" can use a
+//! `ResourceNameTemplate`, e.g. `mrn:tycho:opa:*:${account}:member/${principal}`,
+//! resolved against `TemplateVariables` at evaluation time.
+//!
+//! names headed for logs or third-party sinks can be displayed via
+//! `ResourceName::redacted` (account and path masked by a keyed hash) or
+//! `ResourceName::short` (`{service}:{resource_type}/{last segment}`).
+//!
+//! inventories of one name per line can be parsed in bulk, without a
+//! `String` per line, via `ResourceName::parse_lines` (a byte slice) or
+//! `ResourceName::read_lines` (a `BufRead`).
+//!
+//! workloads holding millions of names can store them in a
+//! `ResourceNameInterner`, which shares the leading segments and resource
+//! types between names and hands out small `Copy` handles.
+//!
+//! errors carry the byte `Span` of the offending input, and can be rendered
+//! against it via `ResourceNameError::render`.
+//!
+//! features:
+//! - `std` (default): `std::error::Error` for errors, `TemplateVariables` for
+//!   `HashMap`, and `ResourceName::read_lines`. without `std` the crate is
+//!   `no_std`, needing only `alloc`.
+//! - `serde`: `Serialize` and `Deserialize` for `ResourceName` (zero-copy) and
+//!   `ResourceNameBuf` (owned), using the canonical string form.
+//! - `arbitrary`: `arbitrary::Arbitrary` for `ResourceNameBuf` (valid names),
+//!   and for `MutatedResourceName` (names broken by a `Mutation`, one per
+//!   parse error), for fuzzing.
+//! - `proptest`: the same, as proptest strategies in `strategy`.
+//! - `simd`: scan for separators eight bytes at a time in
+//!   `ResourceName::parse`, rather than one byte at a time.
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+mod arn;
+mod bulk;
+mod canonical;
+mod diagnostic;
+mod display;
 mod error;
+#[cfg(any(feature = "arbitrary", feature = "proptest", test))]
+mod generate;
+mod hierarchy;
+mod interner;
 mod resource_name;
+mod resource_name_buf;
+mod scan;
+mod schema;
+#[cfg(feature = "serde")]
+mod serde;
+#[cfg(any(feature = "proptest", test))]
+pub mod strategy;
+mod template;
+mod uri;
 
-pub use error::ResourceNameError;
+#[cfg(feature = "std")]
+pub use bulk::ReadLines;
+pub use bulk::{ErrorStats, ParseLines, ParsedLine};
+pub use diagnostic::Diagnostic;
+pub use display::{Redacted, RedactionKey, Short};
+pub use error::{ResourceNameError, Span};
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+pub use generate::{MutatedResourceName, Mutation};
+pub use hierarchy::Ancestors;
+pub use interner::{InternedResourceName, ResourceNameInterner};
 pub use resource_name::ResourceName;
+pub use resource_name_buf::{AsResourceName, ResourceNameBuf};
+pub use schema::{ResourceNameSchema, SchemaResourceName, SchemaSegment};
+pub use template::{ResourceNameTemplate, TemplateVariables};
+pub use uri::URN_NAMESPACE;
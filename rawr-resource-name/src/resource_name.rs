@@ -1,13 +1,17 @@
-use crate::ResourceNameError;
+use alloc::string::{String, ToString};
+use core::cmp::Ordering;
+
+use crate::scan::scan;
+use crate::{ResourceNameBuf, ResourceNameError, Span};
 
 /// the number of segments in a valid resource name.
-const SEGMENT_COUNT: usize = 6;
+pub(crate) const SEGMENT_COUNT: usize = 6;
 
 /// the segment separator in a resource name.
-const SEGMENT_SEPARATOR: &str = ":";
+pub(crate) const SEGMENT_SEPARATOR: &str = ":";
 
 /// the resource path segment separator in a resource name.
-const RESOURCE_PATH_SEGMENT_SEPARATOR: &str = "/";
+pub(crate) const RESOURCE_PATH_SEGMENT_SEPARATOR: &str = "/";
 
 /// a structured resource name, which embeds the following information:
 /// - {prefix} (e.g. 'mrn', 'arn', 'krn', etc.)
@@ -26,112 +30,120 @@ const RESOURCE_PATH_SEGMENT_SEPARATOR: &str = "/";
 ///
 /// note: the {region} and {account_id} segments are optional, and may be
 /// omitted for global resources.
-#[derive(Debug)]
+///
+/// this is the specialised (and fastest) form of `ResourceNameSchema::DEFAULT`,
+/// other shapes can be parsed via their own `ResourceNameSchema`.
+///
+/// equality and hashing are segment-wise. ordering is hierarchical: names sort
+/// by {prefix}, {partition}, {service}, {region}, {account_id} and
+/// {resource_type}, then by {resource_path} one '/' segment at a time, so a
+/// resource is immediately followed by everything nested beneath it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct ResourceName<'a> {
-    prefix: &'a str,
-    partition: &'a str,
-    service: &'a str,
-    region: &'a str,
-    account_id: &'a str,
-    resource_type: &'a str,
-    resource_path: &'a str,
+    pub(crate) prefix: &'a str,
+    pub(crate) partition: &'a str,
+    pub(crate) service: &'a str,
+    pub(crate) region: &'a str,
+    pub(crate) account_id: &'a str,
+    pub(crate) resource_type: &'a str,
+    // what sits between {resource_type} and {resource_path}. always '/' for
+    // rawr-style names, ARNs may also use ':' or nothing at all.
+    pub(crate) resource_separator: &'static str,
+    pub(crate) resource_path: &'a str,
 }
 
 impl<'a> ResourceName<'a> {
     /// parse a `ResourceName` from a string.
+    ///
+    /// the input is scanned exactly once and nothing is allocated. ascii
+    /// control characters are rejected as `InvalidCharacter`, other unwanted
+    /// characters are left to `ResourceName::parse_strict`.
     pub fn parse(input: &'a str) -> Result<Self, ResourceNameError> {
         // did you even bother?
         if input.is_empty() {
             return Err(ResourceNameError::Empty);
         }
 
-        // verify we have the correct structure
-        let mut segments = input.split(SEGMENT_SEPARATOR);
-
-        let prefix = segments
-            .next()
-            .ok_or(ResourceNameError::InvalidSegmentCount {
-                expected: SEGMENT_COUNT,
-                found: 0,
+        // locate every separator (and reject control characters) in a single
+        // pass, everything below is slicing and length checks.
+        let separators =
+            scan(input.as_bytes()).map_err(|offset| ResourceNameError::InvalidCharacter {
+                span: Span::new(offset, offset + 1),
             })?;
 
-        let partition = segments
-            .next()
-            .ok_or(ResourceNameError::InvalidSegmentCount {
-                expected: SEGMENT_COUNT,
-                found: 1,
-            })?;
-
-        let service = segments
-            .next()
-            .ok_or(ResourceNameError::InvalidSegmentCount {
-                expected: SEGMENT_COUNT,
-                found: 2,
-            })?;
-
-        let region = segments
-            .next()
-            .ok_or(ResourceNameError::InvalidSegmentCount {
-                expected: SEGMENT_COUNT,
-                found: 3,
-            })?;
-
-        let account_id = segments
-            .next()
-            .ok_or(ResourceNameError::InvalidSegmentCount {
+        // verify we have the correct structure, i.e. exactly the right number
+        // of segments.
+        if separators.colon_count < SEGMENT_COUNT - 1 {
+            return Err(ResourceNameError::InvalidSegmentCount {
                 expected: SEGMENT_COUNT,
-                found: 4,
-            })?;
-
-        let qualified_resource_path =
-            segments
-                .next()
-                .ok_or(ResourceNameError::InvalidSegmentCount {
-                    expected: SEGMENT_COUNT,
-                    found: 5,
-                })?;
-
-        // verify we don't have any more segments, this is the same kind of
-        // error as above - it's an invalid number of segments.
-        // NOTE: the magic 1 is because segments.next() advances the iterator,
-        // so we need to account for the current segment.
-        if segments.next().is_some() {
-            let remaining_segments = segments.count();
+                found: separators.colon_count + 1,
+                span: Span::at(input.len()),
+            });
+        }
+        if let Some(extra) = separators.extra {
+            // point at everything from the first unexpected separator onwards
             return Err(ResourceNameError::InvalidSegmentCount {
                 expected: SEGMENT_COUNT,
-                found: SEGMENT_COUNT + remaining_segments + 1, // 1 for the current segment
+                found: separators.colon_count + 1,
+                span: Span::new(extra, input.len()),
             });
         }
 
+        // every separator is ascii, so these are all char boundaries.
+        let [c0, c1, c2, c3, c4] = separators.colons;
+        let sep = SEGMENT_SEPARATOR.len();
+        let prefix = &input[..c0];
+        let partition = &input[c0 + sep..c1];
+        let service = &input[c1 + sep..c2];
+        let region = &input[c2 + sep..c3];
+        let account_id = &input[c3 + sep..c4];
+        let qualified_resource_path = &input[c4 + sep..];
+
         // verify we have our required segments, where a value must always be
         // present. (e.g. a global resource name may omit the region).
         // optional segments:
         // - region
         // - account_id
         if prefix.is_empty() {
-            return Err(ResourceNameError::EmptyPrefix);
+            return Err(ResourceNameError::EmptyPrefix {
+                span: Span::of(input, prefix),
+            });
         }
         if partition.is_empty() {
-            return Err(ResourceNameError::EmptyPartition);
+            return Err(ResourceNameError::EmptyPartition {
+                span: Span::of(input, partition),
+            });
         }
         if service.is_empty() {
-            return Err(ResourceNameError::EmptyService);
+            return Err(ResourceNameError::EmptyService {
+                span: Span::of(input, service),
+            });
         }
         if qualified_resource_path.is_empty() {
-            return Err(ResourceNameError::EmptyQualifiedResourcePath);
+            return Err(ResourceNameError::EmptyQualifiedResourcePath {
+                span: Span::of(input, qualified_resource_path),
+            });
         }
 
         // verify we have a valid qualified resource path
-        let (resource_type, resource_path) = qualified_resource_path
-            .split_once(RESOURCE_PATH_SEGMENT_SEPARATOR)
-            .ok_or(ResourceNameError::EmptyQualifiedResourcePath)?;
+        let slash = separators
+            .slash
+            .ok_or(ResourceNameError::EmptyQualifiedResourcePath {
+                span: Span::of(input, qualified_resource_path),
+            })?;
+        let resource_type = &input[c4 + sep..slash];
+        let resource_path = &input[slash + RESOURCE_PATH_SEGMENT_SEPARATOR.len()..];
 
         // verify we have a valid resource type and resource path
         if resource_type.is_empty() {
-            return Err(ResourceNameError::EmptyResourceType);
+            return Err(ResourceNameError::EmptyResourceType {
+                span: Span::of(input, resource_type),
+            });
         }
         if resource_path.is_empty() {
-            return Err(ResourceNameError::EmptyResourcePath);
+            return Err(ResourceNameError::EmptyResourcePath {
+                span: Span::of(input, resource_path),
+            });
         }
 
         Ok(ResourceName {
@@ -141,27 +153,94 @@ impl<'a> ResourceName<'a> {
             region,
             account_id,
             resource_type,
+            resource_separator: RESOURCE_PATH_SEGMENT_SEPARATOR,
             resource_path,
         })
     }
 
+    /// the {prefix} segment (e.g. 'mrn').
+    pub fn prefix(&self) -> &'a str {
+        self.prefix
+    }
+
+    /// the {partition} segment (e.g. 'tycho').
+    pub fn partition(&self) -> &'a str {
+        self.partition
+    }
+
+    /// the {service} segment (e.g. 'opa').
+    pub fn service(&self) -> &'a str {
+        self.service
+    }
+
+    /// the {region} segment, empty for global resources.
+    pub fn region(&self) -> &'a str {
+        self.region
+    }
+
+    /// the {account_id} segment, empty for global resources.
+    pub fn account_id(&self) -> &'a str {
+        self.account_id
+    }
+
+    /// the {resource_type} segment (e.g. 'member').
+    pub fn resource_type(&self) -> &'a str {
+        self.resource_type
+    }
+
+    /// the {resource_path} segment (e.g. 'anderson-dawes').
+    pub fn resource_path(&self) -> &'a str {
+        self.resource_path
+    }
+
     /// convert a `ResourceName` to a string.
     pub fn as_str(&self) -> String {
         self.to_string()
     }
+
+    /// copy a `ResourceName` into an owned `ResourceNameBuf`.
+    pub fn to_buf(&self) -> ResourceNameBuf {
+        ResourceNameBuf::from(self)
+    }
+}
+
+impl Ord for ResourceName<'_> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.prefix
+            .cmp(other.prefix)
+            .then_with(|| self.partition.cmp(other.partition))
+            .then_with(|| self.service.cmp(other.service))
+            .then_with(|| self.region.cmp(other.region))
+            .then_with(|| self.account_id.cmp(other.account_id))
+            .then_with(|| self.resource_type.cmp(other.resource_type))
+            .then_with(|| self.resource_separator.cmp(other.resource_separator))
+            .then_with(|| {
+                // segment-wise, so `a/b` sorts before `a-b` despite '-' < '/'
+                self.resource_path
+                    .split(RESOURCE_PATH_SEGMENT_SEPARATOR)
+                    .cmp(other.resource_path.split(RESOURCE_PATH_SEGMENT_SEPARATOR))
+            })
+    }
+}
+
+impl PartialOrd for ResourceName<'_> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
 }
 
-impl<'a> std::fmt::Display for ResourceName<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<'a> core::fmt::Display for ResourceName<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
-            "{prefix}:{partition}:{service}:{region}:{account_id}:{resource_type}/{resource_path}",
+            "{prefix}:{partition}:{service}:{region}:{account_id}:{resource_type}{resource_separator}{resource_path}",
             prefix = self.prefix,
             partition = self.partition,
             service = self.service,
             region = self.region,
             account_id = self.account_id,
             resource_type = self.resource_type,
+            resource_separator = self.resource_separator,
             resource_path = self.resource_path
         )
     }
@@ -169,6 +248,8 @@ impl<'a> std::fmt::Display for ResourceName<'a> {
 
 #[cfg(test)]
 mod tests {
+    use alloc::string::ToString;
+
     use super::super::*;
     use crate::resource_name::SEGMENT_COUNT;
 
@@ -230,6 +311,7 @@ mod tests {
             ResourceNameError::InvalidSegmentCount {
                 expected: SEGMENT_COUNT,
                 found: 4,
+                span: Span::at(49),
             }
         );
     }
@@ -239,7 +321,10 @@ mod tests {
         let s = ":tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes";
         let result = ResourceName::parse(s);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), ResourceNameError::EmptyPrefix);
+        assert_eq!(
+            result.unwrap_err(),
+            ResourceNameError::EmptyPrefix { span: Span::at(0) }
+        );
     }
 
     #[test]
@@ -247,7 +332,10 @@ mod tests {
         let s = "mrn::opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes";
         let result = ResourceName::parse(s);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), ResourceNameError::EmptyPartition);
+        assert_eq!(
+            result.unwrap_err(),
+            ResourceNameError::EmptyPartition { span: Span::at(4) }
+        );
     }
 
     #[test]
@@ -255,7 +343,10 @@ mod tests {
         let s = "mrn:tycho::sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes";
         let result = ResourceName::parse(s);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), ResourceNameError::EmptyService);
+        assert_eq!(
+            result.unwrap_err(),
+            ResourceNameError::EmptyService { span: Span::at(10) }
+        );
     }
 
     #[test]
@@ -265,7 +356,7 @@ mod tests {
         assert!(result.is_err());
         assert_eq!(
             result.unwrap_err(),
-            ResourceNameError::EmptyQualifiedResourcePath
+            ResourceNameError::EmptyQualifiedResourcePath { span: Span::at(53) }
         );
     }
 
@@ -274,7 +365,10 @@ mod tests {
         let s = "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:/anderson-dawes";
         let result = ResourceName::parse(s);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), ResourceNameError::EmptyResourceType);
+        assert_eq!(
+            result.unwrap_err(),
+            ResourceNameError::EmptyResourceType { span: Span::at(53) }
+        );
     }
 
     #[test]
@@ -282,7 +376,10 @@ mod tests {
         let s = "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/";
         let result = ResourceName::parse(s);
         assert!(result.is_err());
-        assert_eq!(result.unwrap_err(), ResourceNameError::EmptyResourcePath);
+        assert_eq!(
+            result.unwrap_err(),
+            ResourceNameError::EmptyResourcePath { span: Span::at(60) }
+        );
     }
 
     #[test]
@@ -294,7 +391,8 @@ mod tests {
             result.unwrap_err(),
             ResourceNameError::InvalidSegmentCount {
                 expected: SEGMENT_COUNT,
-                found: 11
+                found: 11,
+                span: Span::new(25, 97),
             }
         );
     }
@@ -0,0 +1,375 @@
+use alloc::string::{String, ToString};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+use core::str::FromStr;
+
+use crate::resource_name::SEGMENT_SEPARATOR;
+use crate::{ResourceName, ResourceNameError};
+
+/// the number of leading segments whose lengths we track, everything after
+/// them is the {resource_path}.
+const TRACKED_SEGMENT_COUNT: usize = 6;
+
+/// an owned `ResourceName`, for when a resource name needs to outlive the
+/// input it was parsed from (e.g. deserialized payloads, caches, etc.).
+///
+/// the canonical string is stored once, alongside the length of each leading
+/// segment, so borrowing it back as a `ResourceName` is just slicing - no
+/// re-parsing required.
+#[derive(Debug, Clone)]
+pub struct ResourceNameBuf {
+    inner: String,
+    // {prefix}, {partition}, {service}, {region}, {account_id} and
+    // {resource_type}, in that order.
+    lengths: [usize; TRACKED_SEGMENT_COUNT],
+    resource_separator: &'static str,
+}
+
+impl ResourceNameBuf {
+    /// parse a `ResourceNameBuf` from a string, either a resource name or an
+    /// ARN (see `ResourceName::parse_arn`).
+    pub fn parse(input: &str) -> Result<Self, ResourceNameError> {
+        ResourceName::parse_any(input).map(|rn| ResourceNameBuf::from(&rn))
+    }
+
+    /// borrow a `ResourceNameBuf` as a `ResourceName`.
+    pub fn as_resource_name(&self) -> ResourceName<'_> {
+        // every tracked segment is followed by a ':', except {resource_type}
+        // which is followed by the resource separator, so we can just walk
+        // the lengths.
+        let mut rest = self.inner.as_str();
+        let mut segments = [""; TRACKED_SEGMENT_COUNT];
+        for (idx, (segment, len)) in segments.iter_mut().zip(self.lengths).enumerate() {
+            let separator_len = if idx == TRACKED_SEGMENT_COUNT - 1 {
+                self.resource_separator.len()
+            } else {
+                SEGMENT_SEPARATOR.len()
+            };
+            let (head, tail) = rest.split_at(len);
+            *segment = head;
+            rest = &tail[separator_len..];
+        }
+
+        ResourceName {
+            prefix: segments[0],
+            partition: segments[1],
+            service: segments[2],
+            region: segments[3],
+            account_id: segments[4],
+            resource_type: segments[5],
+            resource_separator: self.resource_separator,
+            resource_path: rest,
+        }
+    }
+
+    /// the canonical string form of the resource name.
+    pub fn as_str(&self) -> &str {
+        &self.inner
+    }
+
+    /// consume the `ResourceNameBuf`, returning the canonical string.
+    pub fn into_string(self) -> String {
+        self.inner
+    }
+}
+
+impl<'a> From<&ResourceName<'a>> for ResourceNameBuf {
+    fn from(rn: &ResourceName<'a>) -> Self {
+        ResourceNameBuf {
+            inner: rn.to_string(),
+            lengths: [
+                rn.prefix.len(),
+                rn.partition.len(),
+                rn.service.len(),
+                rn.region.len(),
+                rn.account_id.len(),
+                rn.resource_type.len(),
+            ],
+            resource_separator: rn.resource_separator,
+        }
+    }
+}
+
+impl<'a> From<ResourceName<'a>> for ResourceNameBuf {
+    fn from(rn: ResourceName<'a>) -> Self {
+        ResourceNameBuf::from(&rn)
+    }
+}
+
+impl TryFrom<String> for ResourceNameBuf {
+    type Error = ResourceNameError;
+
+    /// parse a `ResourceNameBuf` from a string, either a resource name or an
+    /// ARN as `ResourceNameBuf::parse` does, re-using its allocation.
+    fn try_from(input: String) -> Result<Self, Self::Error> {
+        let rn = ResourceName::parse_any(&input)?;
+        let lengths = [
+            rn.prefix.len(),
+            rn.partition.len(),
+            rn.service.len(),
+            rn.region.len(),
+            rn.account_id.len(),
+            rn.resource_type.len(),
+        ];
+
+        let resource_separator = rn.resource_separator;
+
+        Ok(ResourceNameBuf {
+            inner: input,
+            lengths,
+            resource_separator,
+        })
+    }
+}
+
+impl FromStr for ResourceNameBuf {
+    type Err = ResourceNameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        ResourceNameBuf::parse(s)
+    }
+}
+
+impl core::fmt::Display for ResourceNameBuf {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&self.inner)
+    }
+}
+
+/// anything which can be viewed as a `ResourceName`.
+///
+/// this mostly exists so maps keyed by `ResourceNameBuf` can be queried with a
+/// borrowed `ResourceName`, without allocating:
+///
+/// ```
+/// use std::collections::HashMap;
+/// use rawr_resource_name::{AsResourceName, ResourceName, ResourceNameBuf};
+///
+/// let mut owners: HashMap<ResourceNameBuf, &str> = HashMap::new();
+/// owners.insert("mrn:tycho:opa:::member/anderson-dawes".parse().unwrap(), "opa");
+///
+/// let rn = ResourceName::parse("mrn:tycho:opa:::member/anderson-dawes").unwrap();
+/// assert_eq!(owners.get(&rn as &dyn AsResourceName), Some(&"opa"));
+/// ```
+pub trait AsResourceName {
+    /// view `self` as a `ResourceName`.
+    fn as_resource_name(&self) -> ResourceName<'_>;
+}
+
+impl AsResourceName for ResourceName<'_> {
+    fn as_resource_name(&self) -> ResourceName<'_> {
+        *self
+    }
+}
+
+impl AsResourceName for ResourceNameBuf {
+    fn as_resource_name(&self) -> ResourceName<'_> {
+        ResourceNameBuf::as_resource_name(self)
+    }
+}
+
+impl<'a> Borrow<dyn AsResourceName + 'a> for ResourceNameBuf {
+    fn borrow(&self) -> &(dyn AsResourceName + 'a) {
+        self
+    }
+}
+
+// everything below defers to `ResourceName`, so the owned, borrowed and
+// type-erased forms all agree on equality, hashing and ordering.
+
+impl PartialEq for dyn AsResourceName + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_resource_name() == other.as_resource_name()
+    }
+}
+
+impl Eq for dyn AsResourceName + '_ {}
+
+impl Hash for dyn AsResourceName + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_resource_name().hash(state)
+    }
+}
+
+impl PartialOrd for dyn AsResourceName + '_ {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for dyn AsResourceName + '_ {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_resource_name().cmp(&other.as_resource_name())
+    }
+}
+
+impl PartialEq for ResourceNameBuf {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_resource_name() == other.as_resource_name()
+    }
+}
+
+impl Eq for ResourceNameBuf {}
+
+impl Hash for ResourceNameBuf {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_resource_name().hash(state)
+    }
+}
+
+impl PartialOrd for ResourceNameBuf {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ResourceNameBuf {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.as_resource_name().cmp(&other.as_resource_name())
+    }
+}
+
+impl PartialEq<ResourceName<'_>> for ResourceNameBuf {
+    fn eq(&self, other: &ResourceName<'_>) -> bool {
+        self.as_resource_name() == *other
+    }
+}
+
+impl PartialEq<ResourceNameBuf> for ResourceName<'_> {
+    fn eq(&self, other: &ResourceNameBuf) -> bool {
+        *self == other.as_resource_name()
+    }
+}
+
+impl PartialOrd<ResourceName<'_>> for ResourceNameBuf {
+    fn partial_cmp(&self, other: &ResourceName<'_>) -> Option<Ordering> {
+        Some(self.as_resource_name().cmp(other))
+    }
+}
+
+impl PartialOrd<ResourceNameBuf> for ResourceName<'_> {
+    fn partial_cmp(&self, other: &ResourceNameBuf) -> Option<Ordering> {
+        Some(self.cmp(&other.as_resource_name()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    extern crate std;
+
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use std::collections::hash_map::DefaultHasher;
+    use std::collections::{BTreeMap, HashMap};
+
+    use super::*;
+    use crate::Span;
+
+    const VALID_RESOURCE_NAME_KONNECT: &str = "krn:konnect:mesh:eu:df40c456-7dbb-4fbf-8b2c-a1c89997b7c4:control-plane/157807aa-3a85-4504-8340-ad9c0baae569/zone/mhq-eu-primary";
+
+    #[test]
+    fn round_trip_through_resource_name() {
+        let buf = ResourceNameBuf::parse(VALID_RESOURCE_NAME_KONNECT).unwrap();
+        let rn = buf.as_resource_name();
+        assert_eq!(rn.prefix(), "krn");
+        assert_eq!(rn.partition(), "konnect");
+        assert_eq!(rn.service(), "mesh");
+        assert_eq!(rn.region(), "eu");
+        assert_eq!(rn.account_id(), "df40c456-7dbb-4fbf-8b2c-a1c89997b7c4");
+        assert_eq!(rn.resource_type(), "control-plane");
+        assert_eq!(
+            rn.resource_path(),
+            "157807aa-3a85-4504-8340-ad9c0baae569/zone/mhq-eu-primary"
+        );
+        assert_eq!(buf.as_str(), VALID_RESOURCE_NAME_KONNECT);
+    }
+
+    #[test]
+    fn empty_optional_segments_survive() {
+        let buf = ResourceNameBuf::try_from(String::from("mrn:tycho:opa:::member/anderson-dawes"))
+            .unwrap();
+        let rn = buf.as_resource_name();
+        assert_eq!(rn.region(), "");
+        assert_eq!(rn.account_id(), "");
+        assert_eq!(rn.resource_type(), "member");
+        assert_eq!(rn.resource_path(), "anderson-dawes");
+    }
+
+    #[test]
+    fn owned_and_borrowed_agree() {
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME_KONNECT).unwrap();
+        let buf = rn.to_buf();
+        assert_eq!(buf, rn);
+        assert_eq!(rn, buf);
+        assert_eq!(buf.partial_cmp(&rn), Some(Ordering::Equal));
+
+        let hash = |value: &dyn AsResourceName| {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        };
+        let mut hasher = DefaultHasher::new();
+        buf.hash(&mut hasher);
+        assert_eq!(hasher.finish(), hash(&rn));
+    }
+
+    #[test]
+    fn lookup_owned_keys_with_borrowed_names() {
+        let mut map: HashMap<ResourceNameBuf, u32> = HashMap::new();
+        map.insert(
+            ResourceNameBuf::parse(VALID_RESOURCE_NAME_KONNECT).unwrap(),
+            42,
+        );
+
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME_KONNECT).unwrap();
+        assert_eq!(map.get(&rn as &dyn AsResourceName), Some(&42));
+
+        let mut tree: BTreeMap<ResourceNameBuf, u32> = BTreeMap::new();
+        tree.insert(rn.to_buf(), 42);
+        assert_eq!(tree.get(&rn as &dyn AsResourceName), Some(&42));
+
+        let other = ResourceName::parse("mrn:tycho:opa:::member/anderson-dawes").unwrap();
+        assert_eq!(map.get(&other as &dyn AsResourceName), None);
+        assert_eq!(tree.get(&other as &dyn AsResourceName), None);
+    }
+
+    #[test]
+    fn ordering_groups_hierarchically() {
+        let mut names: Vec<ResourceNameBuf> = [
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres-2",
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres/bay-12",
+            "mrn:tycho:mcrn:sol-belt-1:36UeVtK7:station/ceres",
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres",
+            "mrn:tycho:opa:::member/anderson-dawes",
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres/bay-12",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        names.sort();
+        names.dedup();
+
+        let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+        assert_eq!(
+            names,
+            vec![
+                "mrn:tycho:mcrn:sol-belt-1:36UeVtK7:station/ceres",
+                "mrn:tycho:opa:::member/anderson-dawes",
+                "mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres",
+                "mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres/bay-12",
+                "mrn:tycho:opa:sol-belt-1:36UeVtK7:station/ceres-2",
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_input_is_rejected() {
+        let result = "mrn:tycho::sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes"
+            .parse::<ResourceNameBuf>();
+        assert_eq!(
+            result.unwrap_err(),
+            ResourceNameError::EmptyService { span: Span::at(10) }
+        );
+    }
+}
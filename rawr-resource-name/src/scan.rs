@@ -0,0 +1,188 @@
+use crate::resource_name::SEGMENT_COUNT;
+
+/// the segment separator, as a byte.
+const COLON: u8 = b':';
+
+/// the resource path separator, as a byte.
+const SLASH: u8 = b'/';
+
+/// the ascii DEL character, the only control character above the C0 range.
+const DELETE: u8 = 0x7F;
+
+/// where the separators of a resource name are, found in a single pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Separators {
+    /// the offsets of the first `SEGMENT_COUNT - 1` ':' separators.
+    pub(crate) colons: [usize; SEGMENT_COUNT - 1],
+    /// the total number of ':' separators (including any extra ones).
+    pub(crate) colon_count: usize,
+    /// the offset of the first ':' beyond the expected segment count.
+    pub(crate) extra: Option<usize>,
+    /// the offset of the first '/' in the {resource_type}/{resource_path}
+    /// segment.
+    pub(crate) slash: Option<usize>,
+}
+
+impl Separators {
+    const fn new() -> Self {
+        Separators {
+            colons: [0; SEGMENT_COUNT - 1],
+            colon_count: 0,
+            extra: None,
+            slash: None,
+        }
+    }
+
+    /// record a byte of interest at `offset`, returning `Err(offset)` if it's
+    /// an ascii control character.
+    #[inline(always)]
+    fn visit(&mut self, offset: usize, byte: u8) -> Result<(), usize> {
+        match byte {
+            COLON => {
+                if self.colon_count < self.colons.len() {
+                    self.colons[self.colon_count] = offset;
+                } else if self.extra.is_none() {
+                    self.extra = Some(offset);
+                }
+                self.colon_count += 1;
+            }
+            SLASH if self.colon_count == self.colons.len() && self.slash.is_none() => {
+                self.slash = Some(offset);
+            }
+            _ if is_control(byte) => return Err(offset),
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// whether `byte` is an ascii control character (C0 or DEL). no multi-byte
+/// utf-8 sequence contains such a byte, so checking bytes is exact.
+#[inline(always)]
+const fn is_control(byte: u8) -> bool {
+    byte < 0x20 || byte == DELETE
+}
+
+/// locate every separator in `input` and reject ascii control characters, in
+/// a single pass. `Err` carries the offset of the first control character.
+#[inline]
+pub(crate) fn scan(input: &[u8]) -> Result<Separators, usize> {
+    #[cfg(feature = "simd")]
+    return scan_swar(input);
+
+    #[cfg(not(feature = "simd"))]
+    return scan_bytes(input);
+}
+
+/// the byte-at-a-time scanner.
+#[cfg(any(not(feature = "simd"), test))]
+fn scan_bytes(input: &[u8]) -> Result<Separators, usize> {
+    let mut separators = Separators::new();
+    for (offset, &byte) in input.iter().enumerate() {
+        separators.visit(offset, byte)?;
+    }
+    Ok(separators)
+}
+
+/// a word with every byte set to `byte`.
+#[cfg(any(feature = "simd", test))]
+const fn splat(byte: u8) -> u64 {
+    u64::from_ne_bytes([byte; 8])
+}
+
+/// the high bit of every byte of `word` which is zero, and nothing else.
+#[cfg(any(feature = "simd", test))]
+const fn zero_bytes(word: u64) -> u64 {
+    const LOW_SEVEN: u64 = splat(0x7F);
+    !(((word & LOW_SEVEN) + LOW_SEVEN) | word | LOW_SEVEN)
+}
+
+/// the SIMD-within-a-register scanner: eight bytes are tested at once, and
+/// only the bytes of interest are visited.
+#[cfg(any(feature = "simd", test))]
+fn scan_swar(input: &[u8]) -> Result<Separators, usize> {
+    const WORD: usize = size_of::<u64>();
+
+    let mut separators = Separators::new();
+    let mut chunks = input.chunks_exact(WORD);
+    for (idx, chunk) in chunks.by_ref().enumerate() {
+        let word = u64::from_le_bytes(chunk.try_into().expect("chunk is a word"));
+        let mut interesting = zero_bytes(word ^ splat(COLON))
+            | zero_bytes(word ^ splat(SLASH))
+            | zero_bytes(word & splat(0xE0)) // < 0x20
+            | zero_bytes(word ^ splat(DELETE));
+
+        while interesting != 0 {
+            let lane = interesting.trailing_zeros() as usize / 8;
+            let offset = idx * WORD + lane;
+            separators.visit(offset, input[offset])?;
+            interesting &= interesting - 1;
+        }
+    }
+
+    let base = input.len() - chunks.remainder().len();
+    for (lane, &byte) in chunks.remainder().iter().enumerate() {
+        separators.visit(base + lane, byte)?;
+    }
+    Ok(separators)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_bytes_is_exact() {
+        for byte in 0..=u8::MAX {
+            let word = u64::from_le_bytes([1, byte, 0, byte, 0xFF, 0, 0x80, byte]);
+            let expected = u64::from_le_bytes([
+                0,
+                if byte == 0 { 0x80 } else { 0 },
+                0x80,
+                if byte == 0 { 0x80 } else { 0 },
+                0,
+                0x80,
+                0,
+                if byte == 0 { 0x80 } else { 0 },
+            ]);
+            assert_eq!(zero_bytes(word), expected, "byte {byte:#x}");
+        }
+    }
+
+    #[test]
+    fn scanners_agree() {
+        for input in [
+            "",
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes",
+            "mrn:tycho:opa:::member/anderson-dawes",
+            "mrn:tycho:opa:::member",
+            "mrn/tycho/opa:::member:/x/y:z:",
+            "mrn:ティコ:opa:::member/anderson\u{7f}-dawes",
+            "mrn:tycho:opa:::member/anderson-dawes\n",
+            "mrn:tycho:opa:::member/\u{1b}",
+            "::::::::::::::::::::::",
+        ] {
+            assert_eq!(
+                scan_bytes(input.as_bytes()),
+                scan_swar(input.as_bytes()),
+                "{input:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn scan_locates_separators() {
+        let input = "mrn:tycho:opa:sol-belt-1:36UeVtK7:member/ceres/bay-12:x:y";
+        let separators = scan(input.as_bytes()).unwrap();
+        assert_eq!(separators.colons, [3, 9, 13, 24, 33]);
+        assert_eq!(separators.colon_count, 7);
+        assert_eq!(separators.extra, Some(53));
+        assert_eq!(separators.slash, Some(40));
+    }
+
+    #[test]
+    fn scan_rejects_control_characters() {
+        assert_eq!(scan(b"mrn:tycho\t:opa"), Err(9));
+        assert_eq!(scan(b"mrn:tycho:opa\x7f"), Err(13));
+    }
+}
@@ -0,0 +1,482 @@
+use crate::resource_name::{RESOURCE_PATH_SEGMENT_SEPARATOR, SEGMENT_SEPARATOR};
+use crate::scan::scan;
+use crate::{ResourceNameError, Span};
+
+/// a single named segment within a `ResourceNameSchema`.
+///
+/// each segment carries the separator which _introduces_ it. for the first
+/// segment that's a literal the name must begin with (e.g. `//` for
+/// GCP-style names), which is usually empty.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaSegment {
+    name: &'static str,
+    separator: &'static str,
+    optional: bool,
+}
+
+impl SchemaSegment {
+    /// a segment which must always have a value.
+    pub const fn required(separator: &'static str, name: &'static str) -> Self {
+        SchemaSegment {
+            name,
+            separator,
+            optional: false,
+        }
+    }
+
+    /// a segment which may be left empty (e.g. {region} for global resources).
+    pub const fn optional(separator: &'static str, name: &'static str) -> Self {
+        SchemaSegment {
+            name,
+            separator,
+            optional: true,
+        }
+    }
+
+    /// the name of the segment, used for lookups and error reporting.
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// the separator which introduces the segment.
+    pub fn separator(&self) -> &'static str {
+        self.separator
+    }
+
+    /// whether the segment may be left empty.
+    pub fn is_optional(&self) -> bool {
+        self.optional
+    }
+}
+
+/// the shape of a resource name: an ordered list of named segments and the
+/// separators between them.
+///
+/// each segment ends at the first occurrence of the separator introducing the
+/// next one, and the final segment takes whatever remains of the input. a
+/// value may contain separators which only introduce later segments (e.g. a
+/// `/` in the region of a `DEFAULT` name), but not those of its own or earlier
+/// segments - those count as extra segments. the final segment may contain its
+/// own separator, so resource paths can nest.
+///
+/// segments are counted (e.g. by `InvalidSegmentCount`) as they're written:
+/// the separator introducing the second segment divides the name, and a
+/// segment introduced by any other separator is part of the one before it.
+/// so a `DEFAULT` name has six segments, as it does for `ResourceName::parse`,
+/// `{resource_type}/{resource_path}` being one. ascii control characters are
+/// rejected as `InvalidCharacter`.
+///
+/// example, a 5-segment URN:
+/// ```
+/// use rawr_resource_name::{ResourceNameSchema, SchemaSegment};
+///
+/// const URN: ResourceNameSchema<5> = ResourceNameSchema::new([
+///     SchemaSegment::required("", "scheme"),
+///     SchemaSegment::required(":", "org"),
+///     SchemaSegment::required(":", "service"),
+///     SchemaSegment::required(":", "resource_type"),
+///     SchemaSegment::required("/", "resource_path"),
+/// ]);
+///
+/// let rn = URN.parse("urn:tycho:opa:member/anderson-dawes").unwrap();
+/// assert_eq!(rn.get("service"), Some("opa"));
+/// assert_eq!(rn.get("resource_path"), Some("anderson-dawes"));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResourceNameSchema<const N: usize> {
+    segments: [SchemaSegment; N],
+}
+
+impl ResourceNameSchema<7> {
+    /// the rawr resource name format, the same shape `ResourceName` parses:
+    /// `{prefix}:{partition}:{service}:{region}:{account_id}:{resource_type}/{resource_path}`
+    pub const DEFAULT: ResourceNameSchema<7> = ResourceNameSchema::new([
+        SchemaSegment::required("", "prefix"),
+        SchemaSegment::required(SEGMENT_SEPARATOR, "partition"),
+        SchemaSegment::required(SEGMENT_SEPARATOR, "service"),
+        SchemaSegment::optional(SEGMENT_SEPARATOR, "region"),
+        SchemaSegment::optional(SEGMENT_SEPARATOR, "account_id"),
+        SchemaSegment::required(SEGMENT_SEPARATOR, "resource_type"),
+        SchemaSegment::required(RESOURCE_PATH_SEGMENT_SEPARATOR, "resource_path"),
+    ]);
+}
+
+impl Default for ResourceNameSchema<7> {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl<const N: usize> ResourceNameSchema<N> {
+    /// define a schema from its segments.
+    ///
+    /// panics (at compile time, for `const` schemas) if there are no
+    /// segments, or if any segment after the first has an empty separator.
+    pub const fn new(segments: [SchemaSegment; N]) -> Self {
+        assert!(N > 0, "a resource name schema needs at least one segment");
+
+        let mut idx = 1;
+        while idx < N {
+            assert!(
+                !segments[idx].separator.is_empty(),
+                "only the first schema segment may have an empty separator"
+            );
+            idx += 1;
+        }
+
+        ResourceNameSchema { segments }
+    }
+
+    /// the segments of the schema, in order.
+    pub fn segments(&self) -> &[SchemaSegment; N] {
+        &self.segments
+    }
+
+    /// parse a resource name according to this schema. the result borrows
+    /// from `input`, nothing is allocated.
+    pub fn parse<'s, 'a>(
+        &'s self,
+        input: &'a str,
+    ) -> Result<SchemaResourceName<'s, 'a, N>, ResourceNameError> {
+        // did you even bother?
+        if input.is_empty() {
+            return Err(ResourceNameError::Empty);
+        }
+        scan(input.as_bytes()).map_err(|offset| ResourceNameError::InvalidCharacter {
+            span: Span::new(offset, offset + 1),
+        })?;
+
+        let leader = self.segments[0].separator;
+        let body = input
+            .strip_prefix(leader)
+            .ok_or(ResourceNameError::InvalidLeader {
+                expected: leader,
+                span: Span::at(0),
+            })?;
+
+        // every segment but the last ends where the next one's separator
+        // begins, the last one takes whatever is left.
+        let mut rest = body;
+        let mut values = [""; N];
+        for (value, next) in values.iter_mut().zip(&self.segments[1..]) {
+            let Some((head, tail)) = rest.split_once(next.separator) else {
+                // a missing separator within a segment is a missing value,
+                // rather than a missing segment.
+                if next.separator != self.segments[1].separator {
+                    return Err(ResourceNameError::EmptySegment {
+                        segment: next.name,
+                        span: Span::at(input.len()),
+                    });
+                }
+                return Err(ResourceNameError::InvalidSegmentCount {
+                    expected: self.segment_count(),
+                    found: body.matches(next.separator).count() + 1,
+                    span: Span::at(input.len()),
+                });
+            };
+            *value = head;
+            rest = tail;
+        }
+        values[N - 1] = rest;
+
+        // a separator hiding inside a value means the input has more segments
+        // than the schema allows, if it's one the parser has already moved
+        // past. separators which only introduce later segments are part of
+        // the value (e.g. a `/` in a region), and the final segment may nest
+        // with its own separator.
+        let mut extra = 0;
+        let mut first_extra = input.len();
+        for (idx, value) in values.iter().enumerate() {
+            for separator in self.passed_separators(idx) {
+                for (offset, _) in value.match_indices(separator) {
+                    extra += 1;
+                    first_extra = first_extra.min(Span::of(input, value).start + offset);
+                }
+            }
+        }
+        if extra > 0 {
+            return Err(ResourceNameError::InvalidSegmentCount {
+                expected: self.segment_count(),
+                found: self.segment_count() + extra,
+                span: Span::new(first_extra, input.len()),
+            });
+        }
+
+        // verify we have our required segments
+        for (segment, value) in self.segments.iter().zip(values) {
+            if value.is_empty() && !segment.optional {
+                return Err(ResourceNameError::EmptySegment {
+                    segment: segment.name,
+                    span: Span::of(input, value),
+                });
+            }
+        }
+
+        Ok(SchemaResourceName {
+            schema: self,
+            values,
+        })
+    }
+
+    /// how many segments a name has as written, i.e. those introduced by the
+    /// same separator as the second segment, plus the first.
+    fn segment_count(&self) -> usize {
+        let divider = self.segments.get(1).map(|segment| segment.separator);
+        1 + self.segments[1..]
+            .iter()
+            .filter(|segment| Some(segment.separator) == divider)
+            .count()
+    }
+
+    /// the distinct separators which introduce the segments up to and
+    /// including `idx`, the leader is excluded. the final segment's own
+    /// separator is too, since it may nest.
+    fn passed_separators(&self, idx: usize) -> impl Iterator<Item = &'static str> + '_ {
+        let nests = (idx == N - 1).then_some(self.segments[N - 1].separator);
+        self.segments[..=idx]
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(move |(pos, segment)| {
+                Some(segment.separator) != nests
+                    && !self.segments[1..*pos]
+                        .iter()
+                        .any(|earlier| earlier.separator == segment.separator)
+            })
+            .map(|(_, segment)| segment.separator)
+    }
+}
+
+/// a resource name parsed according to a `ResourceNameSchema`.
+#[derive(Debug, Clone, Copy)]
+pub struct SchemaResourceName<'s, 'a, const N: usize> {
+    schema: &'s ResourceNameSchema<N>,
+    values: [&'a str; N],
+}
+
+impl<'s, 'a, const N: usize> SchemaResourceName<'s, 'a, N> {
+    /// the schema the name was parsed with.
+    pub fn schema(&self) -> &'s ResourceNameSchema<N> {
+        self.schema
+    }
+
+    /// look up a segment value by its schema name.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.schema
+            .segments
+            .iter()
+            .position(|segment| segment.name == name)
+            .map(|idx| self.values[idx])
+    }
+
+    /// the segment values, in schema order.
+    pub fn values(&self) -> &[&'a str; N] {
+        &self.values
+    }
+}
+
+impl<const N: usize> core::fmt::Display for SchemaResourceName<'_, '_, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        for (segment, value) in self.schema.segments.iter().zip(self.values) {
+            f.write_str(segment.separator)?;
+            f.write_str(value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+    use crate::ResourceName;
+
+    const GCP: ResourceNameSchema<4> = ResourceNameSchema::new([
+        SchemaSegment::required("//", "service"),
+        SchemaSegment::required("/", "collection"),
+        SchemaSegment::required("/", "project"),
+        SchemaSegment::required("/", "resource_path"),
+    ]);
+
+    #[test]
+    fn default_schema_agrees_with_resource_name() {
+        for input in [
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes",
+            "mrn:tycho:opa:::member/anderson-dawes",
+            "krn:konnect:mesh:eu:df40c456-7dbb-4fbf-8b2c-a1c89997b7c4:control-plane/157807aa-3a85-4504-8340-ad9c0baae569/zone/mhq-eu-primary",
+        ] {
+            let rn = ResourceName::parse(input).unwrap();
+            let srn = ResourceNameSchema::DEFAULT.parse(input).unwrap();
+            assert_eq!(
+                srn.values(),
+                &[
+                    rn.prefix(),
+                    rn.partition(),
+                    rn.service(),
+                    rn.region(),
+                    rn.account_id(),
+                    rn.resource_type(),
+                    rn.resource_path(),
+                ]
+            );
+            assert_eq!(srn.to_string(), input);
+        }
+    }
+
+    #[test]
+    fn default_schema_and_resource_name_agree_on_separators() {
+        for input in [
+            "mrn:tycho:opa:us/east:acct:member/x",
+            "mrn:tycho:opa:us/east:acct/1:member/x",
+            "mrn/1:tycho:opa:::member/x/y",
+            "mrn:tycho:opa:::member/x:y",
+            "mrn:tycho:opa:::member:x/y",
+            "mrn:tycho:opa:::member",
+            "mrn:tycho:opa:::/x",
+            "mrn:tycho:opa:::member/",
+        ] {
+            let rn = ResourceName::parse(input);
+            let srn = ResourceNameSchema::DEFAULT.parse(input);
+            match (rn, srn) {
+                (Ok(rn), Ok(srn)) => assert_eq!(
+                    srn.values(),
+                    &[
+                        rn.prefix(),
+                        rn.partition(),
+                        rn.service(),
+                        rn.region(),
+                        rn.account_id(),
+                        rn.resource_type(),
+                        rn.resource_path(),
+                    ],
+                    "{input}"
+                ),
+                (Err(_), Err(_)) => {}
+                (rn, srn) => panic!("{input}: {rn:?} != {srn:?}"),
+            }
+        }
+
+        let srn = ResourceNameSchema::DEFAULT
+            .parse("mrn:tycho:opa:us/east:acct:member/x")
+            .unwrap();
+        assert_eq!(srn.get("region"), Some("us/east"));
+    }
+
+    #[test]
+    fn default_schema_rejects_extra_segments() {
+        let s = "mrn:foo:tycho:bar:opa:baz:sol-belt-1:buzz:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes:extra";
+        let err = ResourceNameSchema::DEFAULT.parse(s).unwrap_err();
+        assert_eq!(
+            err,
+            ResourceNameError::InvalidSegmentCount {
+                expected: 6,
+                found: 11,
+                span: Span::new(25, 97),
+            }
+        );
+        assert_eq!(err, ResourceName::parse(s).unwrap_err());
+    }
+
+    #[test]
+    fn default_schema_counts_segments_like_resource_name() {
+        for input in [
+            "mrn:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77",
+            "mrn:tycho:opa:::member/x:y",
+            "mrn:tycho:opa:::member:x/y",
+            "mrn:tycho:opa:sol-belt-1:36UeVtK7:x:y:z/w",
+        ] {
+            assert_eq!(
+                ResourceNameSchema::DEFAULT.parse(input).unwrap_err(),
+                ResourceName::parse(input).unwrap_err(),
+                "{input}"
+            );
+        }
+
+        // a missing '/' is a missing resource path, not a missing segment
+        assert_eq!(
+            ResourceNameSchema::DEFAULT
+                .parse("mrn:tycho:opa:::member")
+                .unwrap_err(),
+            ResourceNameError::EmptySegment {
+                segment: "resource_path",
+                span: Span::at(22),
+            }
+        );
+        assert_eq!(
+            GCP.parse("//storage/projects").unwrap_err(),
+            ResourceNameError::InvalidSegmentCount {
+                expected: 4,
+                found: 2,
+                span: Span::at(18),
+            }
+        );
+    }
+
+    #[test]
+    fn default_schema_rejects_control_characters() {
+        for input in [
+            "mrn:a:b:c:d:x/y\n",
+            "mrn:a:b\t:c:d:x/y",
+            "mrn:a:b:c:d:x/\u{7f}",
+        ] {
+            let err = ResourceNameSchema::DEFAULT.parse(input).unwrap_err();
+            assert!(
+                matches!(err, ResourceNameError::InvalidCharacter { .. }),
+                "{input:?}"
+            );
+            assert_eq!(err, ResourceName::parse(input).unwrap_err(), "{input:?}");
+        }
+    }
+
+    #[test]
+    fn default_schema_rejects_missing_segments() {
+        let result =
+            ResourceNameSchema::DEFAULT.parse("mrn:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77");
+        assert_eq!(
+            result.unwrap_err(),
+            ResourceNameError::InvalidSegmentCount {
+                expected: 6,
+                found: 3,
+                span: Span::at(42),
+            }
+        );
+    }
+
+    #[test]
+    fn default_schema_rejects_empty_required_segment() {
+        let result = ResourceNameSchema::DEFAULT
+            .parse("mrn:tycho::sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes");
+        assert_eq!(
+            result.unwrap_err(),
+            ResourceNameError::EmptySegment {
+                segment: "service",
+                span: Span::at(10),
+            }
+        );
+    }
+
+    #[test]
+    fn gcp_style_schema() {
+        let input = "//storage.googleapis.com/projects/rocinante/buckets/logs/objects/bridge";
+        let rn = GCP.parse(input).unwrap();
+        assert_eq!(rn.get("service"), Some("storage.googleapis.com"));
+        assert_eq!(rn.get("collection"), Some("projects"));
+        assert_eq!(rn.get("project"), Some("rocinante"));
+        assert_eq!(rn.get("resource_path"), Some("buckets/logs/objects/bridge"));
+        assert_eq!(rn.get("region"), None);
+        assert_eq!(rn.to_string(), input);
+    }
+
+    #[test]
+    fn gcp_style_schema_requires_leader() {
+        let result = GCP.parse("storage.googleapis.com/projects/rocinante/buckets/logs");
+        assert_eq!(
+            result.unwrap_err(),
+            ResourceNameError::InvalidLeader {
+                expected: "//",
+                span: Span::at(0),
+            }
+        );
+    }
+}
@@ -0,0 +1,169 @@
+//! serde support, enabled via the `serde` feature.
+//!
+//! resource names are (de)serialized as their canonical string form. the
+//! borrowed `ResourceName<'de>` only deserializes from input that can lend us
+//! a `&'de str` (e.g. `serde_json::from_str`), use `ResourceNameBuf` when the
+//! input is transient (e.g. `serde_json::from_reader`, escaped strings).
+//!
+//! both accept ARNs as well, so names from `ResourceName::parse_arn` round
+//! trip.
+use alloc::string::String;
+use core::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::{ResourceName, ResourceNameBuf};
+
+impl Serialize for ResourceName<'_> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_str(self)
+    }
+}
+
+impl Serialize for ResourceNameBuf {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+struct ResourceNameVisitor;
+
+impl<'de> Visitor<'de> for ResourceNameVisitor {
+    type Value = ResourceName<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a borrowed resource name string")
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'de str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        ResourceName::parse_any(v).map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for ResourceName<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_str(ResourceNameVisitor)
+    }
+}
+
+struct ResourceNameBufVisitor;
+
+impl Visitor<'_> for ResourceNameBufVisitor {
+    type Value = ResourceNameBuf;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("a resource name string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        ResourceNameBuf::parse(v).map_err(E::custom)
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        ResourceNameBuf::try_from(v).map_err(E::custom)
+    }
+}
+
+impl<'de> Deserialize<'de> for ResourceNameBuf {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_string(ResourceNameBufVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use super::*;
+
+    const VALID_RESOURCE_NAME: &str =
+        "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes";
+    const VALID_RESOURCE_NAME_JSON: &str =
+        r#""mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes""#;
+
+    #[test]
+    fn serialize_as_canonical_string() {
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME).unwrap();
+        assert_eq!(
+            serde_json::to_string(&rn).unwrap(),
+            VALID_RESOURCE_NAME_JSON
+        );
+
+        let buf = rn.to_buf();
+        assert_eq!(
+            serde_json::to_string(&buf).unwrap(),
+            VALID_RESOURCE_NAME_JSON
+        );
+    }
+
+    #[test]
+    fn deserialize_borrowed() {
+        let rn: ResourceName<'_> = serde_json::from_str(VALID_RESOURCE_NAME_JSON).unwrap();
+        assert_eq!(rn.service(), "opa");
+        assert_eq!(rn.resource_path(), "anderson-dawes");
+
+        // zero-copy means we point straight into the input
+        let input_range = VALID_RESOURCE_NAME_JSON.as_bytes().as_ptr_range();
+        assert!(input_range.contains(&rn.resource_path().as_ptr()));
+    }
+
+    #[test]
+    fn deserialize_borrowed_rejects_transient_input() {
+        // escapes force serde_json to hand us a transient string
+        let result = serde_json::from_str::<ResourceName<'_>>(
+            r#""mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member\/anderson-dawes""#,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn deserialize_owned() {
+        let buf: ResourceNameBuf =
+            serde_json::from_reader(VALID_RESOURCE_NAME_JSON.as_bytes()).unwrap();
+        assert_eq!(buf.as_str(), VALID_RESOURCE_NAME);
+    }
+
+    #[test]
+    fn arns_round_trip() {
+        let rn = ResourceName::parse_arn("arn:aws:sqs:us-east-1:123456789012:queue-name").unwrap();
+        let json = serde_json::to_string(&rn).unwrap();
+
+        let borrowed: ResourceName<'_> = serde_json::from_str(&json).unwrap();
+        assert_eq!(borrowed, rn);
+        let owned: ResourceNameBuf = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(owned, rn);
+    }
+
+    #[test]
+    fn deserialize_error_carries_detail() {
+        let input = r#""mrn:tycho::sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes""#;
+
+        let err = serde_json::from_str::<ResourceName<'_>>(input).unwrap_err();
+        assert!(err.to_string().contains("missing service in resource name"));
+
+        let err = serde_json::from_str::<ResourceNameBuf>(input).unwrap_err();
+        assert!(err.to_string().contains("missing service in resource name"));
+    }
+}
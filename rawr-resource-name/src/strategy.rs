@@ -0,0 +1,106 @@
+//! proptest strategies, enabled via the `proptest` feature.
+//!
+//! `resource_name` produces valid names, `mutated_resource_name` produces
+//! near-valid names which fail `ResourceName::parse` in a known way.
+use alloc::string::String;
+use core::ops::RangeInclusive;
+
+use proptest::arbitrary::Arbitrary;
+use proptest::collection::vec;
+use proptest::prelude::{BoxedStrategy, Just, Strategy, prop_oneof};
+use proptest::sample::select;
+
+use crate::ResourceNameBuf;
+use crate::generate::{
+    Generated, PATH_ALPHABET, PATH_SEGMENTS, PREFIX_ALPHABET, PREFIX_LEN, SEGMENT_ALPHABET,
+    SEGMENT_LEN,
+};
+pub use crate::generate::{MutatedResourceName, Mutation};
+
+/// a segment of `len` characters from `alphabet`.
+fn segment(alphabet: &'static [u8], len: RangeInclusive<usize>) -> impl Strategy<Value = String> {
+    vec(select(alphabet), len).prop_map(|bytes| bytes.into_iter().map(char::from).collect())
+}
+
+/// a segment which is empty for global resources.
+fn optional_segment() -> impl Strategy<Value = String> {
+    prop_oneof![Just(String::new()), segment(SEGMENT_ALPHABET, SEGMENT_LEN)]
+}
+
+/// valid resource names.
+pub fn resource_name() -> impl Strategy<Value = ResourceNameBuf> {
+    (
+        segment(PREFIX_ALPHABET, PREFIX_LEN),
+        segment(SEGMENT_ALPHABET, SEGMENT_LEN),
+        segment(SEGMENT_ALPHABET, SEGMENT_LEN),
+        optional_segment(),
+        optional_segment(),
+        segment(SEGMENT_ALPHABET, SEGMENT_LEN),
+        vec(segment(PATH_ALPHABET, SEGMENT_LEN), PATH_SEGMENTS),
+    )
+        .prop_map(
+            |(prefix, partition, service, region, account_id, resource_type, resource_path)| {
+                Generated {
+                    prefix,
+                    partition,
+                    service,
+                    region,
+                    account_id,
+                    resource_type,
+                    resource_path,
+                }
+                .into_buf()
+            },
+        )
+}
+
+/// every `Mutation`.
+pub fn mutation() -> impl Strategy<Value = Mutation> {
+    select(&Mutation::ALL[..])
+}
+
+/// valid resource names, broken by a `Mutation`.
+pub fn mutated_resource_name() -> impl Strategy<Value = MutatedResourceName> {
+    (resource_name(), mutation())
+        .prop_map(|(rn, mutation)| MutatedResourceName::new(&rn.as_resource_name(), mutation))
+}
+
+impl Arbitrary for ResourceNameBuf {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Self>;
+
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        resource_name().boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::string::ToString;
+
+    use proptest::prelude::{prop_assert_eq, proptest};
+
+    use super::*;
+    use crate::ResourceName;
+
+    proptest! {
+        #[test]
+        fn parse_display_round_trips(rn in resource_name()) {
+            let displayed = rn.as_resource_name().to_string();
+            prop_assert_eq!(ResourceName::parse(&displayed).unwrap(), rn.as_resource_name());
+            prop_assert_eq!(ResourceNameBuf::parse(&displayed).unwrap(), rn);
+        }
+
+        #[test]
+        fn mutations_fail_as_expected(mutated in mutated_resource_name()) {
+            let err = ResourceName::parse(&mutated.input).unwrap_err();
+            prop_assert_eq!(err.kind(), mutated.mutation.expected_kind());
+        }
+
+        #[test]
+        fn uri_component_round_trips(rn in resource_name()) {
+            let encoded = rn.as_resource_name().to_uri_component();
+            prop_assert_eq!(ResourceName::from_uri_component(&encoded).unwrap(), rn);
+        }
+    }
+}
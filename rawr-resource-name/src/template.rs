@@ -0,0 +1,389 @@
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+#[cfg(feature = "std")]
+use core::hash::Hash;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+use crate::resource_name::{RESOURCE_PATH_SEGMENT_SEPARATOR, SEGMENT_SEPARATOR};
+use crate::{ResourceName, ResourceNameBuf, ResourceNameError, Span};
+
+/// opens a template variable.
+const VARIABLE_OPEN: &str = "${";
+
+/// closes a template variable.
+const VARIABLE_CLOSE: &str = "}";
+
+/// stands in for every byte of a variable when validating the shape of a
+/// template, so error spans line up with the template itself.
+const PROBE_CHARACTER: char = 'x';
+
+/// somewhere to look up the values of template variables, e.g. an
+/// authorization request.
+pub trait TemplateVariables {
+    /// the value of the variable `name`, if there is one.
+    fn get(&self, name: &str) -> Option<&str>;
+}
+
+#[cfg(feature = "std")]
+impl<K, V, S> TemplateVariables for HashMap<K, V, S>
+where
+    K: Borrow<str> + Hash + Eq,
+    V: AsRef<str>,
+    S: core::hash::BuildHasher,
+{
+    fn get(&self, name: &str) -> Option<&str> {
+        HashMap::get(self, name).map(AsRef::as_ref)
+    }
+}
+
+impl<K, V> TemplateVariables for BTreeMap<K, V>
+where
+    K: Borrow<str> + Ord,
+    V: AsRef<str>,
+{
+    fn get(&self, name: &str) -> Option<&str> {
+        BTreeMap::get(self, name).map(AsRef::as_ref)
+    }
+}
+
+impl TemplateVariables for [(&str, &str)] {
+    fn get(&self, name: &str) -> Option<&str> {
+        self.iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+/// a piece of a `ResourceNameTemplate`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Part<'a> {
+    Literal(&'a str),
+    Variable { name: &'a str, span: Span },
+}
+
+/// a resource name with `${...}` placeholders, resolved into a concrete
+/// `ResourceNameBuf` at evaluation time.
+///
+/// example:
+/// `mrn:tycho:opa:*:${account}:member/${principal}`
+///
+/// template rules:
+/// - placeholders may appear in any segment, any number of times.
+/// - variable names are non-empty and made of alphanumerics, '_', '-' and '.'.
+/// - the template must be a valid resource name once substituted, which is
+///   checked up front by `ResourceNameTemplate::parse`.
+/// - substituted values must be non-empty and may not contain ':' or '/', so
+///   a value can never change the shape of the name it's substituted into.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResourceNameTemplate<'a> {
+    template: &'a str,
+    parts: Vec<Part<'a>>,
+}
+
+impl<'a> ResourceNameTemplate<'a> {
+    /// parse a template, verifying that substitution yields a valid
+    /// `ResourceName`. error spans are relative to `template`.
+    pub fn parse(template: &'a str) -> Result<Self, ResourceNameError> {
+        let mut parts = Vec::new();
+
+        let mut rest = template;
+        while let Some(open) = rest.find(VARIABLE_OPEN) {
+            if open > 0 {
+                parts.push(Part::Literal(&rest[..open]));
+            }
+
+            let start = Span::of(template, rest).start + open;
+            let after_open = &rest[open + VARIABLE_OPEN.len()..];
+            let Some(close) = after_open.find(VARIABLE_CLOSE) else {
+                return Err(ResourceNameError::UnterminatedVariable {
+                    span: Span::new(start, template.len()),
+                });
+            };
+
+            let name = &after_open[..close];
+            let span = Span::new(
+                start,
+                start + VARIABLE_OPEN.len() + close + VARIABLE_CLOSE.len(),
+            );
+            let valid_name = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+            if !valid_name {
+                return Err(ResourceNameError::InvalidVariable { span });
+            }
+
+            parts.push(Part::Variable { name, span });
+            rest = &after_open[close + VARIABLE_CLOSE.len()..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Literal(rest));
+        }
+
+        // substitute every variable with a same-length stand-in, any value we
+        // accept at resolve time is shaped the same way.
+        let probe: String = parts
+            .iter()
+            .map(|part| match part {
+                Part::Literal(literal) => literal.to_string(),
+                Part::Variable { span, .. } => PROBE_CHARACTER.to_string().repeat(span.len()),
+            })
+            .collect();
+        ResourceName::parse(&probe)?;
+
+        Ok(ResourceNameTemplate { template, parts })
+    }
+
+    /// the template, as it was parsed.
+    pub fn as_str(&self) -> &'a str {
+        self.template
+    }
+
+    /// whether the template contains no variables at all.
+    pub fn is_static(&self) -> bool {
+        self.variables().next().is_none()
+    }
+
+    /// the names of the variables in the template, in order of appearance
+    /// (including repeats).
+    pub fn variables(&self) -> impl Iterator<Item = &'a str> + '_ {
+        self.parts.iter().filter_map(|part| match part {
+            Part::Variable { name, .. } => Some(*name),
+            Part::Literal(_) => None,
+        })
+    }
+
+    /// substitute every variable from `variables`. error spans are relative
+    /// to the template, pointing at the variable at fault (or, for an error
+    /// in the resolved name, at the variable whose value caused it).
+    pub fn resolve<V>(&self, variables: &V) -> Result<ResourceNameBuf, ResourceNameError>
+    where
+        V: TemplateVariables + ?Sized,
+    {
+        let mut resolved = String::with_capacity(self.template.len());
+        for part in &self.parts {
+            match part {
+                Part::Literal(literal) => resolved.push_str(literal),
+                Part::Variable { name, span } => {
+                    let value = variables
+                        .get(name)
+                        .ok_or(ResourceNameError::UnresolvedVariable { span: *span })?;
+                    if value.is_empty()
+                        || value.contains(SEGMENT_SEPARATOR)
+                        || value.contains(RESOURCE_PATH_SEGMENT_SEPARATOR)
+                    {
+                        return Err(ResourceNameError::InvalidVariableValue { span: *span });
+                    }
+                    resolved.push_str(value);
+                }
+            }
+        }
+
+        ResourceNameBuf::try_from(resolved)
+            .map_err(|err| err.map_span(|span| self.template_span(span, variables)))
+    }
+
+    /// map a span of the name resolved from `variables` back onto the
+    /// template. offsets within a literal shift to where it is in the
+    /// template, and anything within a substituted value widens to cover the
+    /// whole variable.
+    fn template_span<V>(&self, span: Span, variables: &V) -> Span
+    where
+        V: TemplateVariables + ?Sized,
+    {
+        let mut start = None;
+        let mut end = self.template.len();
+        let mut at = 0;
+        for part in &self.parts {
+            let (template, len) = match part {
+                Part::Literal(literal) => (Span::of(self.template, literal), literal.len()),
+                Part::Variable { name, span } => (*span, variables.get(name).map_or(0, str::len)),
+            };
+            let locate = |offset: usize| match part {
+                Part::Literal(_) => Span::at(template.start + offset - at),
+                Part::Variable { .. } => template,
+            };
+
+            if start.is_none() && (at..at + len).contains(&span.start) {
+                let located = locate(span.start);
+                start = Some(located.start);
+                if span.is_empty() {
+                    end = located.end;
+                }
+            }
+            if !span.is_empty() && span.end > at && span.end <= at + len {
+                end = match part {
+                    Part::Literal(_) => locate(span.end).start,
+                    Part::Variable { .. } => template.end,
+                };
+            }
+            at += len;
+        }
+
+        Span::new(start.unwrap_or(self.template.len()), end)
+    }
+}
+
+impl core::fmt::Display for ResourceNameTemplate<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(self.template)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    const TEMPLATE: &str = "mrn:tycho:opa:*:${account}:member/${principal}";
+
+    #[test]
+    fn parse_and_resolve() {
+        let template = ResourceNameTemplate::parse(TEMPLATE).unwrap();
+        assert_eq!(template.as_str(), TEMPLATE);
+        assert_eq!(
+            template.variables().collect::<Vec<_>>(),
+            ["account", "principal"]
+        );
+        assert!(!template.is_static());
+
+        let variables = [
+            ("account", "36UeVtK7fIxhHyD9Dd5gc1XSd77"),
+            ("principal", "anderson-dawes"),
+        ];
+        assert_eq!(
+            template.resolve(&variables[..]).unwrap().as_str(),
+            "mrn:tycho:opa:*:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn resolve_from_hash_map() {
+        let template = ResourceNameTemplate::parse(TEMPLATE).unwrap();
+        let variables = HashMap::from([
+            ("account".to_string(), "36UeVtK7".to_string()),
+            ("principal".to_string(), "naomi-nagata".to_string()),
+        ]);
+        assert_eq!(
+            template.resolve(&variables).unwrap().as_str(),
+            "mrn:tycho:opa:*:36UeVtK7:member/naomi-nagata"
+        );
+    }
+
+    #[test]
+    fn variables_within_and_across_segments() {
+        let template =
+            ResourceNameTemplate::parse("mrn:${partition}:opa:${region}-1::team-${team}/${team}")
+                .unwrap();
+        let variables =
+            BTreeMap::from([("partition", "tycho"), ("region", "sol"), ("team", "opa")]);
+        assert_eq!(
+            template.resolve(&variables).unwrap().as_str(),
+            "mrn:tycho:opa:sol-1::team-opa/opa"
+        );
+    }
+
+    #[test]
+    fn static_templates() {
+        let s = "mrn:tycho:opa:::member/anderson-dawes";
+        let template = ResourceNameTemplate::parse(s).unwrap();
+        assert!(template.is_static());
+        assert_eq!(template.resolve(&[][..]).unwrap().as_str(), s);
+    }
+
+    #[test]
+    fn parse_rejects_malformed_variables() {
+        assert_eq!(
+            ResourceNameTemplate::parse("mrn:tycho:opa:::member/${principal").unwrap_err(),
+            ResourceNameError::UnterminatedVariable {
+                span: Span::new(23, 34)
+            }
+        );
+        assert_eq!(
+            ResourceNameTemplate::parse("mrn:tycho:opa:::member/${}").unwrap_err(),
+            ResourceNameError::InvalidVariable {
+                span: Span::new(23, 26)
+            }
+        );
+        assert_eq!(
+            ResourceNameTemplate::parse("mrn:tycho:opa:::member/${a b}").unwrap_err(),
+            ResourceNameError::InvalidVariable {
+                span: Span::new(23, 29)
+            }
+        );
+    }
+
+    #[test]
+    fn parse_rejects_templates_which_are_not_resource_names() {
+        assert_eq!(
+            ResourceNameTemplate::parse("mrn:tycho::::member/${principal}").unwrap_err(),
+            ResourceNameError::EmptyService { span: Span::at(10) }
+        );
+        assert_eq!(
+            ResourceNameTemplate::parse("mrn:tycho:opa:${region}:member/${principal}").unwrap_err(),
+            ResourceNameError::InvalidSegmentCount {
+                expected: 6,
+                found: 5,
+                span: Span::at(43)
+            }
+        );
+    }
+
+    #[test]
+    fn resolve_rejects_missing_and_invalid_values() {
+        let template = ResourceNameTemplate::parse(TEMPLATE).unwrap();
+        assert_eq!(
+            template
+                .resolve(&[("account", "36UeVtK7")][..])
+                .unwrap_err(),
+            ResourceNameError::UnresolvedVariable {
+                span: Span::new(34, 46)
+            }
+        );
+
+        // values which would change the shape of the name
+        for principal in ["", "anderson-dawes:extra", "anderson/dawes"] {
+            let variables = [("account", "36UeVtK7"), ("principal", principal)];
+            assert_eq!(
+                template.resolve(&variables[..]).unwrap_err(),
+                ResourceNameError::InvalidVariableValue {
+                    span: Span::new(34, 46)
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn resolve_errors_point_into_the_template() {
+        let template = ResourceNameTemplate::parse(TEMPLATE).unwrap();
+
+        // a control character within a value underlines that variable
+        let variables = [("account", "36Ue\nVtK7"), ("principal", "anderson-dawes")];
+        let err = template.resolve(&variables[..]).unwrap_err();
+        assert_eq!(
+            err,
+            ResourceNameError::InvalidCharacter {
+                span: Span::new(16, 26)
+            }
+        );
+        assert_eq!(&TEMPLATE[16..26], "${account}");
+        assert!(err.render(TEMPLATE).to_string().contains("^^^^^^^^^^"));
+
+        // errors within literals shift with the values before them, here
+        // "${service}" is two bytes longer than "opa-long"
+        let template = ResourceNameTemplate::parse("mrn:tycho:${service}:::member/x").unwrap();
+        assert_eq!(
+            template.template_span(Span::new(23, 29), &[("service", "opa-long")][..]),
+            Span::new(25, 31)
+        );
+        assert_eq!(
+            template.template_span(Span::at(22), &[("service", "opa-long")][..]),
+            Span::at(24)
+        );
+    }
+}
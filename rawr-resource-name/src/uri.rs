@@ -0,0 +1,287 @@
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use crate::{ResourceName, ResourceNameBuf, ResourceNameError, Span};
+
+/// the RFC 8141 URN scheme.
+const URN_SCHEME: &str = "urn";
+
+/// the namespace identifier (NID) rawr resource names live under.
+pub const URN_NAMESPACE: &str = "rawr";
+
+/// the delimiter between the URN scheme, NID and namespace specific string.
+const URN_SEPARATOR: char = ':';
+
+/// introduces a percent-encoded byte.
+const PERCENT: u8 = b'%';
+
+/// upper-case hex, as recommended by RFC 3986 section 2.1.
+const HEX_DIGITS: &[u8; 16] = b"0123456789ABCDEF";
+
+/// RFC 3986 unreserved characters, the only ones which mean the same thing in
+/// every part of a URI.
+fn is_unreserved(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~')
+}
+
+/// RFC 8141 namespace specific string characters, minus '?' and '#' which
+/// would start a URN component (or a fragment).
+fn is_urn_safe(byte: u8) -> bool {
+    is_unreserved(byte)
+        || matches!(
+            byte,
+            b'!' | b'$'
+                | b'&'
+                | b'\''
+                | b'('
+                | b')'
+                | b'*'
+                | b'+'
+                | b','
+                | b';'
+                | b'='
+                | b':'
+                | b'@'
+                | b'/'
+        )
+}
+
+/// a `fmt::Write` adapter which percent-encodes every byte `is_safe` rejects.
+struct PercentEncoder<'a> {
+    out: &'a mut String,
+    is_safe: fn(u8) -> bool,
+}
+
+impl Write for PercentEncoder<'_> {
+    fn write_str(&mut self, s: &str) -> core::fmt::Result {
+        for &byte in s.as_bytes() {
+            if (self.is_safe)(byte) {
+                self.out.push(byte as char);
+            } else {
+                self.out.push(PERCENT as char);
+                self.out.push(HEX_DIGITS[usize::from(byte >> 4)] as char);
+                self.out.push(HEX_DIGITS[usize::from(byte & 0xF)] as char);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// percent-decode `input`, where `offset` is where `input` begins within
+/// whatever the caller was handed (for error spans).
+fn percent_decode(input: &str, offset: usize) -> Result<String, ResourceNameError> {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+
+    let mut idx = 0;
+    while idx < bytes.len() {
+        if bytes[idx] != PERCENT {
+            decoded.push(bytes[idx]);
+            idx += 1;
+            continue;
+        }
+
+        let hex = |b: Option<&u8>| b.and_then(|b| (*b as char).to_digit(16));
+        match (hex(bytes.get(idx + 1)), hex(bytes.get(idx + 2))) {
+            (Some(high), Some(low)) => decoded.push((high << 4 | low) as u8),
+            _ => {
+                return Err(ResourceNameError::InvalidPercentEncoding {
+                    span: Span::new(offset + idx, offset + (idx + 3).min(bytes.len())),
+                });
+            }
+        }
+        idx += 3;
+    }
+
+    String::from_utf8(decoded).map_err(|_| ResourceNameError::InvalidPercentEncoding {
+        span: Span::new(offset, offset + input.len()),
+    })
+}
+
+/// URI and URN conversions.
+///
+/// - a URI component percent-encodes everything but RFC 3986 unreserved
+///   characters (`A-Z a-z 0-9 - . _ ~`), so the result is safe as a single
+///   path segment or query value. `:` and `/` become `%3A` and `%2F`.
+/// - a URN is `urn:rawr:{resource_name}` (RFC 8141). `:` and `/` are valid in
+///   a namespace specific string and are left as-is, `?`, `#`, `%` and
+///   anything non-ascii are percent-encoded.
+///
+/// both decoders parse the decoded name as `ResourceNameBuf::parse` does
+/// (ARNs included), so error spans from parsing refer to the _decoded_ name.
+impl ResourceName<'_> {
+    /// encode this resource name as a single URI path segment or query value.
+    pub fn to_uri_component(&self) -> String {
+        let mut out = String::new();
+        let mut encoder = PercentEncoder {
+            out: &mut out,
+            is_safe: is_unreserved,
+        };
+        write!(encoder, "{self}").expect("writing to a String never fails");
+        out
+    }
+
+    /// decode a resource name previously encoded with `to_uri_component`.
+    pub fn from_uri_component(input: &str) -> Result<ResourceNameBuf, ResourceNameError> {
+        ResourceNameBuf::try_from(percent_decode(input, 0)?)
+    }
+
+    /// encode this resource name as an RFC 8141 URN, `urn:rawr:{name}`.
+    pub fn to_urn(&self) -> String {
+        let mut out = format!("{URN_SCHEME}{URN_SEPARATOR}{URN_NAMESPACE}{URN_SEPARATOR}");
+        let mut encoder = PercentEncoder {
+            out: &mut out,
+            is_safe: is_urn_safe,
+        };
+        write!(encoder, "{self}").expect("writing to a String never fails");
+        out
+    }
+
+    /// decode a resource name from a `urn:rawr:{name}` URN. the scheme and
+    /// namespace are case-insensitive, as per RFC 8141.
+    pub fn from_urn(input: &str) -> Result<ResourceNameBuf, ResourceNameError> {
+        let mut parts = input.splitn(3, URN_SEPARATOR);
+        let (Some(scheme), Some(namespace), Some(nss)) = (parts.next(), parts.next(), parts.next())
+        else {
+            return Err(ResourceNameError::InvalidUrn {
+                span: Span::new(0, input.len()),
+            });
+        };
+
+        if !scheme.eq_ignore_ascii_case(URN_SCHEME) {
+            return Err(ResourceNameError::InvalidUrn {
+                span: Span::of(input, scheme),
+            });
+        }
+        if !namespace.eq_ignore_ascii_case(URN_NAMESPACE) {
+            return Err(ResourceNameError::InvalidUrn {
+                span: Span::of(input, namespace),
+            });
+        }
+
+        // r-, q- and f-components aren't part of the name, and we never emit
+        // them, so refuse rather than silently dropping them.
+        let offset = Span::of(input, nss).start;
+        if let Some(idx) = nss.find(['?', '#']) {
+            return Err(ResourceNameError::InvalidUrn {
+                span: Span::new(offset + idx, input.len()),
+            });
+        }
+
+        ResourceNameBuf::try_from(percent_decode(nss, offset)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VALID_RESOURCE_NAME: &str =
+        "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes";
+    const VALID_RESOURCE_NAME_KONNECT: &str = "krn:konnect:mesh:eu:df40c456-7dbb-4fbf-8b2c-a1c89997b7c4:control-plane/157807aa-3a85-4504-8340-ad9c0baae569/zone/mhq-eu-primary";
+
+    #[test]
+    fn uri_component_escapes_separators() {
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME).unwrap();
+        assert_eq!(
+            rn.to_uri_component(),
+            "mrn%3Atycho%3Aopa%3Asol-belt-1%3A36UeVtK7fIxhHyD9Dd5gc1XSd77%3Amember%2Fanderson-dawes"
+        );
+    }
+
+    #[test]
+    fn uri_component_round_trip() {
+        for input in [
+            VALID_RESOURCE_NAME,
+            VALID_RESOURCE_NAME_KONNECT,
+            "mrn:tycho:opa:::member/100% jos\u{e9}?#&=+",
+        ] {
+            let rn = ResourceName::parse(input).unwrap();
+            let encoded = rn.to_uri_component();
+            assert!(encoded.bytes().all(|b| is_unreserved(b) || b == PERCENT));
+            assert_eq!(ResourceName::from_uri_component(&encoded).unwrap(), rn);
+        }
+    }
+
+    #[test]
+    fn uri_component_accepts_lowercase_hex() {
+        let buf =
+            ResourceName::from_uri_component("mrn%3atycho%3aopa%3a%3a%3amember%2fanderson-dawes")
+                .unwrap();
+        assert_eq!(buf.as_str(), "mrn:tycho:opa:::member/anderson-dawes");
+    }
+
+    #[test]
+    fn uri_component_rejects_bad_escapes() {
+        assert_eq!(
+            ResourceName::from_uri_component("mrn%3Atycho%3Aopa%3A%3A%3Amember%2Fanderson%zz")
+                .unwrap_err(),
+            ResourceNameError::InvalidPercentEncoding {
+                span: Span::new(43, 46)
+            }
+        );
+        assert_eq!(
+            ResourceName::from_uri_component("mrn%3Atycho%3Aopa%3A%3A%3Amember%2Fanderson%F")
+                .unwrap_err(),
+            ResourceNameError::InvalidPercentEncoding {
+                span: Span::new(43, 45)
+            }
+        );
+        assert_eq!(
+            ResourceName::from_uri_component("mrn:tycho:opa:::member/%FF").unwrap_err(),
+            ResourceNameError::InvalidPercentEncoding {
+                span: Span::new(0, 26)
+            }
+        );
+    }
+
+    #[test]
+    fn urn_round_trip() {
+        let rn = ResourceName::parse(VALID_RESOURCE_NAME_KONNECT).unwrap();
+        let urn = rn.to_urn();
+        assert_eq!(urn, format!("urn:rawr:{VALID_RESOURCE_NAME_KONNECT}"));
+        assert_eq!(ResourceName::from_urn(&urn).unwrap(), rn);
+
+        let rn = ResourceName::parse("mrn:tycho:opa:::member/100%?#jos\u{e9}").unwrap();
+        let urn = rn.to_urn();
+        assert_eq!(urn, "urn:rawr:mrn:tycho:opa:::member/100%25%3F%23jos%C3%A9");
+        assert_eq!(ResourceName::from_urn(&urn).unwrap(), rn);
+    }
+
+    #[test]
+    fn urn_scheme_and_namespace_are_case_insensitive() {
+        let buf = ResourceName::from_urn("URN:Rawr:mrn:tycho:opa:::member/anderson-dawes").unwrap();
+        assert_eq!(buf.as_str(), "mrn:tycho:opa:::member/anderson-dawes");
+    }
+
+    #[test]
+    fn urn_rejects_foreign_urns() {
+        assert_eq!(
+            ResourceName::from_urn("urn:isbn:0451450523").unwrap_err(),
+            ResourceNameError::InvalidUrn {
+                span: Span::new(4, 8)
+            }
+        );
+        assert_eq!(
+            ResourceName::from_urn("url:rawr:mrn:tycho:opa:::member/anderson-dawes").unwrap_err(),
+            ResourceNameError::InvalidUrn {
+                span: Span::new(0, 3)
+            }
+        );
+        assert_eq!(
+            ResourceName::from_urn("urn:rawr").unwrap_err(),
+            ResourceNameError::InvalidUrn {
+                span: Span::new(0, 8)
+            }
+        );
+        assert_eq!(
+            ResourceName::from_urn("urn:rawr:mrn:tycho:opa:::member/anderson-dawes?=v1")
+                .unwrap_err(),
+            ResourceNameError::InvalidUrn {
+                span: Span::new(46, 50)
+            }
+        );
+    }
+}
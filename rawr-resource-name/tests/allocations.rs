@@ -0,0 +1,90 @@
+//! allocation-counting tests for the zero-allocation APIs, see
+//! `docs/quality.md`.
+
+// `#[global_allocator]` expands into a 4-argument `realloc` shim.
+#![allow(clippy::too_many_arguments)]
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use rawr_resource_name::{ResourceName, ResourceNameSchema};
+
+/// counts allocations made by the current thread, so tests running in
+/// parallel don't see each other's allocations.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// the number of allocations made by `f`.
+fn allocations<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    let after = ALLOCATIONS.with(Cell::get);
+    drop(result);
+    after - before
+}
+
+const INPUTS: &[&str] = &[
+    "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:member/anderson-dawes",
+    "mrn:tycho:opa:::member/anderson-dawes",
+    "mrn:tycho:opa:sol-belt-1:36UeVtK7fIxhHyD9Dd5gc1XSd77:station/ceres/bay-12",
+    // errors are allocation-free too
+    "",
+    "mrn:tycho::::member/anderson-dawes",
+    "mrn:tycho:opa:sol-belt-1:36UeVtK7:member/anderson-dawes:extra",
+    "mrn:tycho:opa:::member/anderson\n-dawes",
+];
+
+#[test]
+fn parse_does_not_allocate() {
+    for input in INPUTS {
+        assert_eq!(allocations(|| ResourceName::parse(input)), 0, "{input:?}");
+    }
+}
+
+#[test]
+fn parse_strict_does_not_allocate() {
+    for input in INPUTS {
+        assert_eq!(
+            allocations(|| ResourceName::parse_strict(input)),
+            0,
+            "{input:?}"
+        );
+    }
+}
+
+#[test]
+fn schema_parse_does_not_allocate() {
+    for input in INPUTS {
+        assert_eq!(
+            allocations(|| ResourceNameSchema::DEFAULT.parse(input)),
+            0,
+            "{input:?}"
+        );
+    }
+}
+
+#[test]
+fn parse_lines_does_not_allocate() {
+    let inventory = INPUTS.join("\n");
+    assert_eq!(
+        allocations(|| ResourceName::parse_lines(inventory.as_bytes()).count()),
+        0
+    );
+}